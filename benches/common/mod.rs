@@ -0,0 +1,51 @@
+//! Shared helpers for the benchmarks, which time the release binary on
+//! scripts written to a temporary directory.
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+pub const BACKENDS: [&str; 2] = ["tree", "vm"];
+
+/// The fastest and the median wall-clock time of a script over several runs.
+#[derive(Clone, Copy)]
+pub struct Timing {
+    pub median: Duration,
+    pub min: Duration,
+}
+
+/// Writes `source` to a temporary script whose name includes `name`.
+pub fn write_script(name: &str, source: &str) -> PathBuf {
+    let file_name = format!("rloxi-bench-{}-{}.lox", name, std::process::id());
+    let path = std::env::temp_dir().join(file_name);
+    fs::write(&path, source).expect("Failed to write benchmark script");
+    return path;
+}
+
+/// Runs the script at `path` on `backend` `runs` times.
+pub fn time_script(backend: &str, path: &PathBuf, runs: usize) -> Timing {
+    let mut times: Vec<Duration> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            let output = Command::new(env!("CARGO_BIN_EXE_rloxi"))
+                .arg(format!("--backend={}", backend))
+                .arg(path)
+                .output()
+                .expect("Failed to run rloxi");
+            assert!(
+                output.status.success(),
+                "benchmark script failed:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    return Timing {
+        median: times[runs / 2],
+        min: times[0],
+    };
+}
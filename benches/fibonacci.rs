@@ -0,0 +1,70 @@
+//! Times the Fibonacci loop from `test.lox`, scaled up so that variable
+//! access dominates: the loop runs many times over and only prints its final
+//! result.
+//!
+//! The loop runs twice: once inside a function, where its variables are
+//! locals read from resolver-assigned slots, and once at the top level, where
+//! they are globals looked up by name in a hash map. Before locals had slots,
+//! every variable was looked up by name like the globals still are, so the
+//! `globals` timing is a stand-in for how the `locals` one used to perform.
+//!
+//! Run with `cargo bench --bench fibonacci`.
+
+mod common;
+
+use std::fs;
+
+use common::{time_script, write_script, BACKENDS};
+
+const RUNS: usize = 5;
+
+const LOCALS: &str = r#"
+fun fibonacci(limit) {
+  var a = 0;
+  var temp;
+
+  for (var b = 1; a < limit; b = temp + b) {
+    temp = a;
+    a = b;
+  }
+  return a;
+}
+
+var result;
+for (var i = 0; i < 50000; i = i + 1) {
+  result = fibonacci(10000);
+}
+print result;
+"#;
+
+const GLOBALS: &str = r#"
+var limit = 10000;
+var a;
+var temp;
+var b;
+
+var result;
+for (var i = 0; i < 50000; i = i + 1) {
+  a = 0;
+  for (b = 1; a < limit; b = temp + b) {
+    temp = a;
+    a = b;
+  }
+  result = a;
+}
+print result;
+"#;
+
+fn main() {
+    for (name, source) in [("locals", LOCALS), ("globals", GLOBALS)] {
+        let path = write_script(&format!("fibonacci-{}", name), source);
+        for backend in BACKENDS {
+            let timing = time_script(backend, &path, RUNS);
+            println!(
+                "fibonacci/{:<7} {:<4} median {:>8.1?}  min {:>8.1?}",
+                name, backend, timing.median, timing.min
+            );
+        }
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,207 @@
+//! Microbenchmarks that each stress one part of the runtime on both backends.
+//!
+//! Run with `cargo bench --bench micro`. Arguments that are not flags select
+//! the cases whose name contains one of them. To catch regressions, record a
+//! baseline with `-- --save-baseline FILE` before a change and compare against
+//! it with `-- --baseline FILE` afterwards; the run fails if any median is
+//! more than `THRESHOLD` percent slower than its recorded value.
+
+mod common;
+
+use std::{collections::HashMap, fs, process::ExitCode, time::Duration};
+
+use common::{time_script, write_script, BACKENDS};
+
+const RUNS: usize = 9;
+
+/// How much slower than the baseline, in percent, a median may get.
+const THRESHOLD: f64 = 10.0;
+
+const CASES: [(&str, &str); 7] = [
+    (
+        "locals",
+        r#"
+fun run() {
+  var a = 1;
+  var b = 2;
+  var sum = 0;
+  for (var i = 0; i < 1000000; i = i + 1) {
+    sum = a + b + sum;
+  }
+  return sum;
+}
+print run();
+"#,
+    ),
+    (
+        "globals",
+        r#"
+var a = 1;
+var sum = 0;
+for (var i = 0; i < 500000; i = i + 1) {
+  sum = sum + a;
+}
+print sum;
+"#,
+    ),
+    (
+        "calls",
+        r#"
+fun add(a, b) { return a + b; }
+var sum = 0;
+for (var i = 0; i < 300000; i = i + 1) {
+  sum = add(sum, i);
+}
+print sum;
+"#,
+    ),
+    (
+        "closures",
+        r#"
+fun counter() {
+  var count = 0;
+  fun increment() {
+    count = count + 1;
+    return count;
+  }
+  return increment;
+}
+var next = counter();
+for (var i = 0; i < 300000; i = i + 1) {
+  next();
+}
+print next();
+"#,
+    ),
+    (
+        "fields",
+        r#"
+class Point {}
+var point = Point();
+point.x = 0;
+for (var i = 0; i < 300000; i = i + 1) {
+  point.x = point.x + 1;
+}
+print point.x;
+"#,
+    ),
+    (
+        "methods",
+        r#"
+class Counter {
+  init() { this.count = 0; }
+  increment() { this.count = this.count + 1; }
+}
+var counter = Counter();
+for (var i = 0; i < 200000; i = i + 1) {
+  counter.increment();
+}
+print counter.count;
+"#,
+    ),
+    (
+        "strings",
+        r#"
+var name = "lox";
+var text;
+for (var i = 0; i < 300000; i = i + 1) {
+  text = name + name;
+}
+print text;
+"#,
+    ),
+];
+
+struct Arguments {
+    filters: Vec<String>,
+    baseline: Option<String>,
+    save_baseline: Option<String>,
+}
+
+fn parse_arguments() -> Arguments {
+    let mut arguments = Arguments {
+        filters: vec![],
+        baseline: None,
+        save_baseline: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => arguments.baseline = args.next(),
+            "--save-baseline" => arguments.save_baseline = args.next(),
+            // Cargo passes `--bench` to every benchmark binary.
+            flag if flag.starts_with("--") => {}
+            _ => arguments.filters.push(arg),
+        }
+    }
+    return arguments;
+}
+
+/// Reads a baseline written by `save_baseline`: one `name nanoseconds` per line.
+fn load_baseline(path: &str) -> HashMap<String, Duration> {
+    let contents = fs::read_to_string(path).expect("Failed to read baseline");
+    return contents
+        .lines()
+        .filter_map(|line| {
+            let (name, nanos) = line.split_once(' ')?;
+            let nanos = nanos.parse().ok()?;
+            Some((String::from(name), Duration::from_nanos(nanos)))
+        })
+        .collect();
+}
+
+fn save_baseline(path: &str, medians: &[(String, Duration)]) {
+    let contents: String = medians
+        .iter()
+        .map(|(name, median)| format!("{} {}\n", name, median.as_nanos()))
+        .collect();
+    fs::write(path, contents).expect("Failed to write baseline");
+}
+
+fn main() -> ExitCode {
+    let arguments = parse_arguments();
+    let baseline = arguments.baseline.as_deref().map(load_baseline);
+
+    let mut medians = vec![];
+    let mut regressed = false;
+    for (case, source) in CASES {
+        let selected = arguments.filters.is_empty()
+            || arguments
+                .filters
+                .iter()
+                .any(|filter| case.contains(filter.as_str()));
+        if !selected {
+            continue;
+        }
+
+        let path = write_script(case, source);
+        for backend in BACKENDS {
+            let name = format!("{}/{}", case, backend);
+            let timing = time_script(backend, &path, RUNS);
+            print!(
+                "{:<18} median {:>8.1?}  min {:>8.1?}",
+                name, timing.median, timing.min
+            );
+
+            if let Some(previous) = baseline.as_ref().and_then(|baseline| baseline.get(&name)) {
+                let change = (timing.median.as_secs_f64() / previous.as_secs_f64() - 1.0) * 100.0;
+                print!("  {:>+6.1}%", change);
+                if change > THRESHOLD {
+                    print!("  regressed");
+                    regressed = true;
+                }
+            }
+            println!();
+            medians.push((name, timing.median));
+        }
+        fs::remove_file(&path).ok();
+    }
+
+    if let Some(path) = &arguments.save_baseline {
+        save_baseline(path, &medians);
+    }
+    if regressed {
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
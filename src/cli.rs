@@ -0,0 +1,115 @@
+use std::io::IsTerminal;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether diagnostics written to stderr should contain ANSI colors.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => std::io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// How diagnostics are written: annotated snippets for people, or one JSON
+/// object per line for tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+/// Which implementation runs the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Walks the syntax tree directly.
+    Tree,
+    /// Compiles to bytecode and runs it on a stack machine.
+    Vm,
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub script: Option<String>,
+    pub color: ColorChoice,
+    pub error_format: ErrorFormat,
+    pub backend: Backend,
+    /// Print the compiled bytecode instead of running the script.
+    pub dump_bytecode: bool,
+    /// Report what the garbage collector did once the script finishes.
+    pub gc_stats: bool,
+    /// An error code to describe instead of running anything.
+    pub explain: Option<String>,
+}
+
+pub const USAGE: &str = "Usage: rloxi [--color=auto|always|never] [--error-format=human|json]
+             [--backend=tree|vm] [--dump-bytecode] [--gc-stats] [script]
+       rloxi --explain <code>";
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            script: None,
+            color: ColorChoice::Auto,
+            error_format: ErrorFormat::Human,
+            backend: Backend::Tree,
+            dump_bytecode: false,
+            gc_stats: false,
+            explain: None,
+        };
+
+        while let Some(arg) = args.next() {
+            if let Some(value) = arg.strip_prefix("--color=") {
+                options.color = match value {
+                    "auto" => ColorChoice::Auto,
+                    "always" => ColorChoice::Always,
+                    "never" => ColorChoice::Never,
+                    _ => return Err(format!("Invalid value for '--color': '{}'.", value)),
+                };
+            } else if let Some(value) = arg.strip_prefix("--error-format=") {
+                options.error_format = match value {
+                    "human" => ErrorFormat::Human,
+                    "json" => ErrorFormat::Json,
+                    _ => return Err(format!("Invalid value for '--error-format': '{}'.", value)),
+                };
+            } else if let Some(value) = arg.strip_prefix("--backend=") {
+                options.backend = match value {
+                    "tree" => Backend::Tree,
+                    "vm" => Backend::Vm,
+                    _ => return Err(format!("Invalid value for '--backend': '{}'.", value)),
+                };
+            } else if arg == "--dump-bytecode" {
+                options.dump_bytecode = true;
+            } else if arg == "--gc-stats" {
+                options.gc_stats = true;
+            } else if arg == "--explain" {
+                let Some(code) = args.next() else {
+                    return Err(String::from("Expected an error code after '--explain'."));
+                };
+                options.explain = Some(code);
+            } else if let Some(code) = arg.strip_prefix("--explain=") {
+                options.explain = Some(String::from(code));
+            } else if arg.starts_with("--") {
+                return Err(format!("Unknown option '{}'.", arg));
+            } else if options.script.is_none() {
+                options.script = Some(arg);
+            } else {
+                return Err(String::from("Expected at most one script."));
+            }
+        }
+
+        return Ok(options);
+    }
+
+    /// The name diagnostics use for the source being run.
+    pub fn source_name(&self) -> &str {
+        return self.script.as_deref().unwrap_or("<prompt>");
+    }
+}
@@ -1,3 +1,93 @@
-pub fn report_error(line: usize, location: &str, message: &str) {
-    eprintln!("[line {line}] Error{location}: {message}");
+use std::fmt::Display;
+
+use crate::{
+    cli::{ErrorFormat, Options},
+    lexing::span::Span,
+};
+
+pub mod explain;
+mod json;
+mod render;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A secondary span pointed at by a diagnostic, with an optional note.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: Option<String>,
+}
+
+impl Label {
+    pub fn new(span: Span, message: Option<String>) -> Self {
+        Self { span, message }
+    }
+}
+
+/// A problem found in a script, in a form shared by every phase so that it can
+/// be reported with its exact source location.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: String, span: Span) -> Self {
+        Self {
+            severity: Severity::Error,
+            code,
+            message,
+            span,
+            labels: vec![],
+        }
+    }
+
+    pub fn with_labels(mut self, labels: Vec<Label>) -> Self {
+        self.labels = labels;
+        return self;
+    }
+}
+
+/// Writes diagnostics for one source text to stderr.
+pub struct Reporter {
+    source: String,
+    name: String,
+    format: ErrorFormat,
+    color: bool,
+}
+
+impl Reporter {
+    pub fn new(source: String, options: &Options) -> Self {
+        Self {
+            source,
+            name: String::from(options.source_name()),
+            format: options.error_format,
+            color: options.color.enabled(),
+        }
+    }
+
+    pub fn report(&self, diagnostic: &Diagnostic) {
+        match self.format {
+            ErrorFormat::Human => eprint!(
+                "{}",
+                render::render(diagnostic, &self.source, &self.name, self.color)
+            ),
+            ErrorFormat::Json => eprintln!("{}", json::render(diagnostic, &self.name)),
+        }
+    }
 }
@@ -0,0 +1,471 @@
+/// The long-form documentation behind an error code, printed by `--explain`.
+struct Explanation {
+    code: &'static str,
+    title: &'static str,
+    description: &'static str,
+    erroneous: &'static str,
+    fixed: &'static str,
+}
+
+const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "E0001",
+        title: "A string literal is missing its closing quote.",
+        description: "String literals start and end with a double quote and may span several \
+                      lines. Triple-quoted strings end at the next `\"\"\"`, and raw strings \
+                      at a quote followed by as many `#`s as came between their `r` and \
+                      their opening quote. The lexer reached the end of the file while still \
+                      inside one.",
+        erroneous: "print \"hello;",
+        fixed: "print \"hello\";",
+    },
+    Explanation {
+        code: "E0002",
+        title: "The source contains a character that is not part of Lox.",
+        description: "Only the characters used by Lox's operators, punctuation, literals and \
+                      identifiers may appear outside of strings and comments.",
+        erroneous: "var price = 10 @ 2;",
+        fixed: "var price = 10 * 2;",
+    },
+    Explanation {
+        code: "E0003",
+        title: "A string literal contains an escape sequence Lox does not know.",
+        description: "Inside strings, a backslash starts one of the escapes `\\n`, `\\t`, \
+                      `\\r`, `\\\\`, `\\\"`, `\\$`, `\\0`, `\\xNN` for an ASCII character given \
+                      by two hex digits, or `\\u{XXXX}` for any Unicode scalar value given by \
+                      one to six hex digits. A backslash at the end of a line continues the \
+                      string on the next line without its line break and leading \
+                      whitespace. Write `\\\\` for a literal backslash.",
+        erroneous: "print \"C:\\data\";",
+        fixed: "print \"C:\\\\data\";",
+    },
+    Explanation {
+        code: "E0004",
+        title: "A number literal has a radix prefix but no digits.",
+        description: "Integers may be written in hexadecimal after `0x`, in binary after \
+                      `0b` or in octal after `0o`. At least one digit of that base must \
+                      follow the prefix.",
+        erroneous: "var mask = 0x;",
+        fixed: "var mask = 0xFF;",
+    },
+    Explanation {
+        code: "E0005",
+        title: "A number literal has an exponent marker but no exponent.",
+        description: "A decimal number may end in an exponent: `e` or `E`, an optional `+` \
+                      or `-`, and at least one digit, as in `1e-9` or `6.02E23`.",
+        erroneous: "var tiny = 1e;",
+        fixed: "var tiny = 1e-9;",
+    },
+    Explanation {
+        code: "E0006",
+        title: "A number literal contains a digit its base does not have.",
+        description: "Binary literals may only use `0` and `1`, octal literals `0` to `7`, \
+                      and hexadecimal literals `0` to `9` and `a` to `f` in either case. \
+                      Letters directly after such a literal are read as part of it.",
+        erroneous: "var flags = 0b102;",
+        fixed: "var flags = 0b101;",
+    },
+    Explanation {
+        code: "E0007",
+        title: "A digit separator is not between two digits.",
+        description: "Digits may be grouped with `_`, as in `1_000_000`, but every `_` must \
+                      have a digit on both sides. It cannot start or end a number, its \
+                      fraction or its exponent, and cannot follow another `_`.",
+        erroneous: "var million = 1_000_000_;",
+        fixed: "var million = 1_000_000;",
+    },
+    Explanation {
+        code: "E0009",
+        title: "A block comment is missing its closing `*/`.",
+        description: "Block comments run from `/*` to the matching `*/` and may nest, so \
+                      every `/*` inside one needs its own `*/` as well. The lexer reached \
+                      the end of the file while still inside the comment the error points at.",
+        erroneous: "/* disabled for now:\n/* print \"old\"; */\nprint \"new\";",
+        fixed: "/* disabled for now:\n/* print \"old\"; */ */\nprint \"new\";",
+    },
+    Explanation {
+        code: "E0101",
+        title: "An opening parenthesis is missing.",
+        description: "`if`, `while` and `for` wrap their condition or clauses in parentheses, \
+                      and the message says which keyword was missing one.",
+        erroneous: "while x < 10 { x = x + 1; }",
+        fixed: "while (x < 10) { x = x + 1; }",
+    },
+    Explanation {
+        code: "E0102",
+        title: "A closing parenthesis is missing.",
+        description: "Every `(` must be matched by a `)`: around conditions, grouped \
+                      expressions, parameter lists and argument lists.",
+        erroneous: "print (1 + 2;",
+        fixed: "print (1 + 2);",
+    },
+    Explanation {
+        code: "E0103",
+        title: "An expression was expected.",
+        description: "The parser needed a value here, such as a literal, a variable, a call \
+                      or a parenthesized expression, but found something else. This often \
+                      means an operator is missing its right-hand operand.",
+        erroneous: "var total = 1 +;",
+        fixed: "var total = 1 + 2;",
+    },
+    Explanation {
+        code: "E0104",
+        title: "A semicolon is missing.",
+        description: "Expression statements, `print`, `var` declarations and `return` all \
+                      end with `;`, as do the first two clauses of a `for` loop.",
+        erroneous: "print \"hi\"\nprint \"there\";",
+        fixed: "print \"hi\";\nprint \"there\";",
+    },
+    Explanation {
+        code: "E0105",
+        title: "A block is missing its closing brace.",
+        description: "A block opened with `{` must be closed with `}` before the end of the \
+                      file.",
+        erroneous: "{\n  print 1;",
+        fixed: "{\n  print 1;\n}",
+    },
+    Explanation {
+        code: "E0106",
+        title: "A variable declaration has no name.",
+        description: "`var` must be followed by the identifier being declared.",
+        erroneous: "var = 1;",
+        fixed: "var count = 1;",
+    },
+    Explanation {
+        code: "E0107",
+        title: "The left-hand side of `=` cannot be assigned to.",
+        description: "Only variables and fields of instances can be assigned. Other \
+                      expressions, such as arithmetic or calls, produce values that have \
+                      nowhere to be stored.",
+        erroneous: "var a = 1;\nvar b = 2;\na + b = 3;",
+        fixed: "var a = 1;\nvar b = 2;\na = 3 - b;",
+    },
+    Explanation {
+        code: "E0108",
+        title: "A function or method declaration has no name.",
+        description: "`fun` and method declarations in a class body must start with the \
+                      identifier being declared.",
+        erroneous: "fun (a) { return a; }",
+        fixed: "fun identity(a) { return a; }",
+    },
+    Explanation {
+        code: "E0109",
+        title: "A parameter list contains something other than a name.",
+        description: "Parameters are plain identifiers separated by commas; they cannot have \
+                      default values or be other expressions.",
+        erroneous: "fun add(a, 1) { return a + 1; }",
+        fixed: "fun add(a, b) { return a + b; }",
+    },
+    Explanation {
+        code: "E0110",
+        title: "A function or method body does not start with a brace.",
+        description: "The body of a function or method is always a block, even when it only \
+                      contains a single statement.",
+        erroneous: "fun twice(n) return n * 2;",
+        fixed: "fun twice(n) { return n * 2; }",
+    },
+    Explanation {
+        code: "E0111",
+        title: "A call passes more than 255 arguments.",
+        description: "Calls are limited to 255 arguments. Group related values into an \
+                      instance and pass that instead.",
+        erroneous: "configure(a1, a2, a3, ..., a256);",
+        fixed: "var settings = Settings();\nsettings.a1 = a1;\nconfigure(settings);",
+    },
+    Explanation {
+        code: "E0112",
+        title: "A function declares more than 255 parameters.",
+        description: "Functions are limited to 255 parameters. Group related values into an \
+                      instance and take that instead.",
+        erroneous: "fun configure(a1, a2, a3, ..., a256) {}",
+        fixed: "fun configure(settings) {}",
+    },
+    Explanation {
+        code: "E0113",
+        title: "A class declaration has no name.",
+        description: "`class` must be followed by the identifier being declared.",
+        erroneous: "class { greet() {} }",
+        fixed: "class Greeter { greet() {} }",
+    },
+    Explanation {
+        code: "E0114",
+        title: "A class body does not start with a brace.",
+        description: "The methods of a class are listed between `{` and `}` after its name \
+                      and optional superclass.",
+        erroneous: "class Greeter\n  greet() {}\n}",
+        fixed: "class Greeter {\n  greet() {}\n}",
+    },
+    Explanation {
+        code: "E0115",
+        title: "A class body is missing its closing brace.",
+        description: "A class body only contains method declarations and must be closed \
+                      with `}`.",
+        erroneous: "class Greeter {\n  greet() {}",
+        fixed: "class Greeter {\n  greet() {}\n}",
+    },
+    Explanation {
+        code: "E0116",
+        title: "A `.` is not followed by a property name.",
+        description: "Property access and assignment name the property with an identifier \
+                      right after the dot.",
+        erroneous: "print point.;",
+        fixed: "print point.x;",
+    },
+    Explanation {
+        code: "E0117",
+        title: "A class inherits from something other than a name.",
+        description: "The superclass after `<` must be written as the name of a class.",
+        erroneous: "class Cat < \"Animal\" {}",
+        fixed: "class Cat < Animal {}",
+    },
+    Explanation {
+        code: "E0118",
+        title: "`super` is not followed by a dot.",
+        description: "`super` cannot be used as a value on its own; it is only used to look \
+                      up a method on the superclass.",
+        erroneous: "class Cat < Animal {\n  speak() { return super; }\n}",
+        fixed: "class Cat < Animal {\n  speak() { return super.speak(); }\n}",
+    },
+    Explanation {
+        code: "E0119",
+        title: "`super.` is not followed by a method name.",
+        description: "After `super.` the parser expects the name of the superclass method to \
+                      look up.",
+        erroneous: "class Cat < Animal {\n  speak() { super.(); }\n}",
+        fixed: "class Cat < Animal {\n  speak() { super.speak(); }\n}",
+    },
+    Explanation {
+        code: "E0120",
+        title: "A local variable is read in its own initializer.",
+        description: "A local variable does not exist until its initializer has finished, so \
+                      the initializer cannot refer to it. Shadowing an outer variable with \
+                      a value computed from it needs a different name.",
+        erroneous: "var a = 1;\n{\n  var a = a + 1;\n}",
+        fixed: "var a = 1;\n{\n  var b = a + 1;\n}",
+    },
+    Explanation {
+        code: "E0121",
+        title: "A name is declared twice in the same local scope.",
+        description: "Inside a block or function, each variable and parameter name can only \
+                      be declared once. Assign to the existing variable instead, or pick \
+                      another name.",
+        erroneous: "fun f() {\n  var a = 1;\n  var a = 2;\n}",
+        fixed: "fun f() {\n  var a = 1;\n  a = 2;\n}",
+    },
+    Explanation {
+        code: "E0122",
+        title: "`return` is used outside of a function.",
+        description: "Top-level code is not inside a function, so there is nothing to return \
+                      from.",
+        erroneous: "return 1;",
+        fixed: "fun one() {\n  return 1;\n}",
+    },
+    Explanation {
+        code: "E0123",
+        title: "An initializer returns a value.",
+        description: "`init` always returns the instance being initialized. It may use a bare \
+                      `return;` to stop early, but not return anything else.",
+        erroneous: "class Point {\n  init(x) { return x; }\n}",
+        fixed: "class Point {\n  init(x) { this.x = x; }\n}",
+    },
+    Explanation {
+        code: "E0124",
+        title: "`this` is used outside of a class.",
+        description: "`this` refers to the instance a method was called on, so it only has a \
+                      meaning inside the methods of a class.",
+        erroneous: "fun name() { return this.name; }",
+        fixed: "class Person {\n  name() { return this.name; }\n}",
+    },
+    Explanation {
+        code: "E0125",
+        title: "`super` is used outside of a class.",
+        description: "`super` looks up methods on the superclass of the enclosing class, so it \
+                      can only appear inside a method.",
+        erroneous: "fun speak() { super.speak(); }",
+        fixed: "class Cat < Animal {\n  speak() { super.speak(); }\n}",
+    },
+    Explanation {
+        code: "E0126",
+        title: "`super` is used in a class that has no superclass.",
+        description: "A class only has a superclass to call into when it is declared with \
+                      `< Superclass`.",
+        erroneous: "class Cat {\n  speak() { super.speak(); }\n}",
+        fixed: "class Cat < Animal {\n  speak() { super.speak(); }\n}",
+    },
+    Explanation {
+        code: "E0127",
+        title: "An interpolated expression is not followed by `}`.",
+        description: "Inside a string, `${` starts an expression whose value is inserted \
+                      into the string, and a `}` must end that expression before the string \
+                      continues. Write `\\${` for a literal `${`.",
+        erroneous: "print \"total: ${price * count\";",
+        fixed: "print \"total: ${price * count}\";",
+    },
+    Explanation {
+        code: "E0201",
+        title: "Unary `-` is applied to something that is not a number.",
+        description: "Negation is only defined for numbers. The value being negated is \
+                      labeled with its type.",
+        erroneous: "print -\"5\";",
+        fixed: "print -5;",
+    },
+    Explanation {
+        code: "E0202",
+        title: "An arithmetic or comparison operator is applied to a non-number.",
+        description: "`-`, `*`, `/`, `<`, `<=`, `>` and `>=` require both operands to be \
+                      numbers. Each operand is labeled with its type.",
+        erroneous: "print 10 - \"3\";",
+        fixed: "print 10 - 3;",
+    },
+    Explanation {
+        code: "E0203",
+        title: "`+` is applied to operands that do not match.",
+        description: "`+` adds two numbers, or concatenates its operands as text when at \
+                      least one of them is a string. Any other pair of values, such as a \
+                      number and `nil`, cannot be added. Each operand is labeled with its \
+                      type.",
+        erroneous: "var bonus;\nprint 100 + bonus;",
+        fixed: "var bonus = 0;\nprint 100 + bonus;",
+    },
+    Explanation {
+        code: "E0204",
+        title: "A variable is used that has not been declared.",
+        description: "Variables must be declared with `var` before they are read or assigned. \
+                      Global variables are looked up when the code runs, so a function may \
+                      refer to a global declared after it, as long as it is called later.",
+        erroneous: "print count;",
+        fixed: "var count = 0;\nprint count;",
+    },
+    Explanation {
+        code: "E0205",
+        title: "Something other than a function or class is called.",
+        description: "Only functions, methods and classes can be called with `()`.",
+        erroneous: "var name = \"lox\";\nname();",
+        fixed: "fun name() { return \"lox\"; }\nname();",
+    },
+    Explanation {
+        code: "E0206",
+        title: "A function is called with the wrong number of arguments.",
+        description: "Calls must pass exactly as many arguments as the function declares \
+                      parameters. Calling a class passes its arguments to `init`.",
+        erroneous: "fun add(a, b) { return a + b; }\nprint add(1);",
+        fixed: "fun add(a, b) { return a + b; }\nprint add(1, 2);",
+    },
+    Explanation {
+        code: "E0207",
+        title: "A property is read that the instance does not have.",
+        description: "Reading a property finds a field set on the instance or a method of its \
+                      class or superclasses. Fields only exist once they have been assigned.",
+        erroneous: "class Point {}\nvar p = Point();\nprint p.x;",
+        fixed: "class Point {}\nvar p = Point();\np.x = 1;\nprint p.x;",
+    },
+    Explanation {
+        code: "E0208",
+        title: "A property is read from something that is not an instance.",
+        description: "Only instances of classes have properties. Numbers, strings, booleans, \
+                      `nil`, functions and classes themselves do not.",
+        erroneous: "var word = \"lox\";\nprint word.length;",
+        fixed: "class Word {}\nvar word = Word();\nword.length = 3;\nprint word.length;",
+    },
+    Explanation {
+        code: "E0209",
+        title: "A field is set on something that is not an instance.",
+        description: "Only instances of classes can hold fields.",
+        erroneous: "var point = 1;\npoint.x = 2;",
+        fixed: "class Point {}\nvar point = Point();\npoint.x = 2;",
+    },
+    Explanation {
+        code: "E0210",
+        title: "A class inherits from something that is not a class.",
+        description: "The name after `<` must refer to a class when the declaration runs.",
+        erroneous: "var Animal = \"animal\";\nclass Cat < Animal {}",
+        fixed: "class Animal {}\nclass Cat < Animal {}",
+    },
+    Explanation {
+        code: "E0211",
+        title: "A class inherits from itself.",
+        description: "A class cannot be its own superclass, since its methods would have \
+                      nothing to fall back on.",
+        erroneous: "class Cat < Cat {}",
+        fixed: "class Animal {}\nclass Cat < Animal {}",
+    },
+    Explanation {
+        code: "E0212",
+        title: "`super` could not be resolved at runtime.",
+        description: "`super` was evaluated without an enclosing subclass method to bind it. \
+                      The resolver rejects such code before it runs (see E0125 and E0126), \
+                      so this error indicates that the program was evaluated without being \
+                      resolved first.",
+        erroneous: "fun speak() { super.speak(); }",
+        fixed: "class Cat < Animal {\n  speak() { super.speak(); }\n}",
+    },
+    Explanation {
+        code: "E0213",
+        title: "Calls are nested too deeply.",
+        description: "Each backend allows 10000 function, method and initializer calls to be \
+                      active at once; the tree-walking backend may stop sooner when the \
+                      calls' bodies nest expressions very deeply. Going past that almost \
+                      always means a recursive function is missing the case that stops it. \
+                      Deep but finite recursion can be rewritten as a loop.",
+        erroneous: "fun countdown(n) {\n  print n;\n  countdown(n - 1);\n}",
+        fixed: "fun countdown(n) {\n  print n;\n  if (n > 0) countdown(n - 1);\n}",
+    },
+    Explanation {
+        code: "E0301",
+        title: "A function uses more than 65536 distinct constants.",
+        description: "With `--backend=vm`, each function's numbers, strings and names are \
+                      stored in a constant pool indexed by 16-bit operands. Split very large \
+                      functions or top-level scripts into smaller functions.",
+        erroneous: "print 1;\nprint 2;\n...\nprint 70000;",
+        fixed: "fun first() { print 1; ... }\nfun second() { print 35001; ... }",
+    },
+    Explanation {
+        code: "E0302",
+        title: "A function declares more than 65536 local variables.",
+        description: "With `--backend=vm`, local variables live in stack slots addressed by \
+                      16-bit operands. Move some of them into separate functions or fields.",
+        erroneous: "{\n  var v1 = 1;\n  ...\n  var v70000 = 1;\n}",
+        fixed: "{\n  var values = Values();\n  values.v1 = 1;\n}",
+    },
+    Explanation {
+        code: "E0303",
+        title: "A closure captures more than 65536 variables.",
+        description: "With `--backend=vm`, the variables a function captures from enclosing \
+                      functions are addressed by 16-bit operands. Capture an instance that \
+                      holds the values instead.",
+        erroneous: "fun outer() {\n  var v1 = 1;\n  ...\n  fun inner() { return v1 + v70000; }\n}",
+        fixed: "fun outer() {\n  var values = Values();\n  fun inner() { return values.v1; }\n}",
+    },
+    Explanation {
+        code: "E0304",
+        title: "A branch or loop body is too large to jump over.",
+        description: "With `--backend=vm`, `if`, `while`, `for`, `and` and `or` compile to \
+                      jumps of at most 65535 bytes. Move part of the body into a function.",
+        erroneous: "while (running) {\n  // tens of thousands of statements\n}",
+        fixed: "fun step() {\n  // tens of thousands of statements\n}\nwhile (running) step();",
+    },
+];
+
+/// The text `rloxi --explain <code>` prints, or `None` for an unknown code.
+pub fn explain(code: &str) -> Option<String> {
+    let explanation = EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.code.eq_ignore_ascii_case(code))?;
+
+    return Some(format!(
+        "{}: {}\n\n{}\n\nErroneous code example:\n\n{}\n\nFixed:\n\n{}\n",
+        explanation.code,
+        explanation.title,
+        explanation.description,
+        indent(explanation.erroneous),
+        indent(explanation.fixed)
+    ));
+}
+
+fn indent(example: &str) -> String {
+    return example
+        .lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<String>>()
+        .join("\n");
+}
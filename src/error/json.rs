@@ -0,0 +1,56 @@
+use crate::lexing::span::Span;
+
+use super::Diagnostic;
+
+/// Renders `diagnostic` as a single-line JSON object. The field names are part
+/// of the tool-facing interface and must stay stable.
+pub fn render(diagnostic: &Diagnostic, name: &str) -> String {
+    let labels: Vec<String> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            let message = match &label.message {
+                Some(message) => string(message),
+                None => String::from("null"),
+            };
+            return format!("{{\"message\":{},\"span\":{}}}", message, span(label.span));
+        })
+        .collect();
+
+    return format!(
+        "{{\"code\":{},\"severity\":{},\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"span\":{},\"labels\":[{}]}}",
+        string(diagnostic.code),
+        string(&diagnostic.severity.to_string()),
+        string(&diagnostic.message),
+        string(name),
+        diagnostic.span.line,
+        diagnostic.span.column,
+        span(diagnostic.span),
+        labels.join(",")
+    );
+}
+
+fn span(span: Span) -> String {
+    return format!(
+        "{{\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}",
+        span.start, span.end, span.line, span.column
+    );
+}
+
+/// Quotes `text` as a JSON string literal.
+fn string(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    return result;
+}
@@ -0,0 +1,199 @@
+use crate::lexing::span::Span;
+
+use super::Diagnostic;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            return format!("{}{}{}", style, text, RESET);
+        }
+        return String::from(text);
+    }
+}
+
+/// One underlined region of a source line.
+struct Mark<'a> {
+    line: usize,
+    column: usize,
+    width: usize,
+    primary: bool,
+    message: Option<&'a str>,
+}
+
+impl<'a> Mark<'a> {
+    fn new(span: Span, source: &str, primary: bool, message: Option<&'a str>) -> Self {
+        let text = source.get(span.start..span.end).unwrap_or("");
+        let width = text.chars().take_while(|c| *c != '\n').count().max(1);
+        Self {
+            line: span.line,
+            column: span.column.max(1),
+            width,
+            primary,
+            message,
+        }
+    }
+
+    fn style(&self) -> &'static str {
+        if self.primary {
+            return RED;
+        }
+        return BLUE;
+    }
+
+    fn symbol(&self) -> char {
+        if self.primary {
+            return '^';
+        }
+        return '-';
+    }
+}
+
+/// Renders `diagnostic` the way rustc does: a header, the location, and each
+/// affected source line with the offending ranges underlined.
+pub fn render(diagnostic: &Diagnostic, source: &str, name: &str, color: bool) -> String {
+    let painter = Painter { color };
+
+    let mut marks = vec![Mark::new(diagnostic.span, source, true, None)];
+    for label in &diagnostic.labels {
+        marks.push(Mark::new(
+            label.span,
+            source,
+            false,
+            label.message.as_deref(),
+        ));
+    }
+    marks.sort_by_key(|mark| (mark.line, mark.column));
+
+    let last_line = marks.iter().map(|mark| mark.line).max().unwrap_or(1);
+    let gutter = " ".repeat(last_line.to_string().len());
+    let bar = painter.paint("|", BLUE);
+
+    let mut output = format!(
+        "{}: {}\n",
+        painter.paint(
+            &format!("{}[{}]", diagnostic.severity, diagnostic.code),
+            RED
+        ),
+        painter.paint(&diagnostic.message, BOLD)
+    );
+    output.push_str(&format!(
+        "{}{} {}:{}:{}\n",
+        gutter,
+        painter.paint("-->", BLUE),
+        name,
+        diagnostic.span.line,
+        diagnostic.span.column
+    ));
+    output.push_str(&format!("{} {}\n", gutter, bar));
+
+    let mut previous_line = None;
+    let mut index = 0;
+    while index < marks.len() {
+        let line = marks[index].line;
+        let end = marks[index..]
+            .iter()
+            .position(|mark| mark.line != line)
+            .map_or(marks.len(), |offset| index + offset);
+        let line_marks = &marks[index..end];
+        index = end;
+
+        if let Some(previous) = previous_line {
+            if line > previous + 1 {
+                output.push_str(&format!("{}\n", painter.paint("...", BLUE)));
+            }
+        }
+        previous_line = Some(line);
+
+        let text = source.lines().nth(line.saturating_sub(1)).unwrap_or("");
+        output.push_str(&format!(
+            "{} {} {}\n",
+            painter.paint(&format!("{:>width$}", line, width = gutter.len()), BLUE),
+            bar,
+            text
+        ));
+        render_marks(&mut output, &painter, &gutter, text, line_marks);
+    }
+
+    output.push('\n');
+    return output;
+}
+
+fn render_marks(output: &mut String, painter: &Painter, gutter: &str, text: &str, marks: &[Mark]) {
+    let bar = painter.paint("|", BLUE);
+
+    let mut underline = String::new();
+    let mut position = 1;
+    for mark in marks {
+        let start = mark.column.max(position);
+        let end = mark.column + mark.width;
+        if start >= end {
+            continue;
+        }
+        underline.push_str(&padding(text, position, start));
+        let symbols: String = std::iter::repeat_n(mark.symbol(), end - start).collect();
+        underline.push_str(&painter.paint(&symbols, mark.style()));
+        position = end;
+    }
+
+    // The rightmost note goes on the underline itself; the others hang below
+    // it, each on its own line, connected to their mark with a `|`.
+    let mut labeled: Vec<&Mark> = marks.iter().filter(|mark| mark.message.is_some()).collect();
+    if let Some(last) = labeled.last() {
+        if last.column >= marks.last().map_or(0, |mark| mark.column) {
+            let message = last.message.unwrap_or("");
+            underline.push(' ');
+            underline.push_str(&painter.paint(message, last.style()));
+            labeled.pop();
+        }
+    }
+    output.push_str(&format!("{} {} {}\n", gutter, bar, underline));
+
+    while let Some(current) = labeled.pop() {
+        let (row, _) = connectors(painter, text, &labeled, Some(current));
+        output.push_str(&format!("{} {} {}\n", gutter, bar, row));
+
+        let (mut row, position) = connectors(painter, text, &labeled, None);
+        row.push_str(&padding(text, position, current.column));
+        row.push_str(&painter.paint(current.message.unwrap_or(""), current.style()));
+        output.push_str(&format!("{} {} {}\n", gutter, bar, row));
+    }
+}
+
+/// A row of `|` connectors under each of `marks` and `extra`, along with the
+/// column just past the last one.
+fn connectors(
+    painter: &Painter,
+    text: &str,
+    marks: &[&Mark],
+    extra: Option<&Mark>,
+) -> (String, usize) {
+    let mut row = String::new();
+    let mut position = 1;
+    for mark in marks.iter().copied().chain(extra) {
+        row.push_str(&padding(text, position, mark.column));
+        row.push_str(&painter.paint("|", mark.style()));
+        position = mark.column + 1;
+    }
+    return (row, position);
+}
+
+/// Whitespace that lines a marker up under column `to`, reusing the source
+/// line's tabs so that the alignment survives any tab width.
+fn padding(text: &str, from: usize, to: usize) -> String {
+    return text
+        .chars()
+        .chain(std::iter::repeat(' '))
+        .skip(from.saturating_sub(1))
+        .take(to.saturating_sub(from))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+}
@@ -0,0 +1,153 @@
+//! A cycle collector for the reference-counted objects of both backends.
+//!
+//! Reference counting frees an object as soon as its last reference goes
+//! away, but never frees a cycle, such as a closure stored in a variable of
+//! the environment it captured. Every object that can be part of a cycle is
+//! therefore tracked here, and a collection finds the unreachable ones by
+//! trial deletion: subtracting the references tracked objects hold to each
+//! other from their reference counts leaves only the references held from
+//! outside the heap. Those objects are the roots: the global environment and
+//! the environments of active calls, or the virtual machine's stack and
+//! globals, along with any value the interpreter itself is holding on to.
+//! Whatever cannot be reached from a root is garbage, and clearing the
+//! references it holds lets reference counting free it.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+/// Identifies a tracked object by the address of its allocation.
+pub type Address = *const ();
+
+/// The number of objects tracked between automatic collections, before the
+/// heap has grown past it.
+const MIN_THRESHOLD: usize = 10_000;
+
+/// An object the collector tracks.
+pub trait Trace {
+    /// Calls `visit` once for every strong reference this object holds to
+    /// another tracked object.
+    fn trace(&self, visit: &mut dyn FnMut(Address));
+
+    /// Drops the references this object holds, so that the cycles it is part
+    /// of fall apart.
+    fn clear(&self);
+}
+
+/// Counters reported by `--gc-stats`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    pub collections: usize,
+    /// Objects tracked since the program started.
+    pub tracked: usize,
+    /// Objects freed by the collector rather than by reference counting.
+    pub freed: usize,
+}
+
+struct Heap {
+    objects: Vec<Weak<dyn Trace>>,
+    /// Objects tracked since the last collection.
+    allocated: usize,
+    threshold: usize,
+    stats: Stats,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap {
+        objects: vec![],
+        allocated: 0,
+        threshold: MIN_THRESHOLD,
+        stats: Stats::default(),
+    });
+}
+
+pub fn address<T: ?Sized>(object: &Rc<T>) -> Address {
+    return Rc::as_ptr(object) as Address;
+}
+
+/// Starts tracking `object`, collecting first if enough objects have been
+/// tracked since the last collection.
+pub fn track<T: Trace + 'static>(object: Rc<T>) -> Rc<T> {
+    let weak = Rc::downgrade(&object);
+    let should_collect = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.objects.push(weak);
+        heap.allocated += 1;
+        heap.stats.tracked += 1;
+        return heap.allocated >= heap.threshold;
+    });
+    if should_collect {
+        collect();
+    }
+    return object;
+}
+
+/// Frees every tracked object that is unreachable from the roots and returns
+/// how many there were.
+pub fn collect() -> usize {
+    let tracked = HEAP.with(|heap| std::mem::take(&mut heap.borrow_mut().objects));
+    let objects: HashMap<Address, Rc<dyn Trace>> = tracked
+        .iter()
+        .filter_map(Weak::upgrade)
+        .map(|object| (address(&object), object))
+        .collect();
+
+    // The upgrade above holds one reference to every object itself.
+    let mut external: HashMap<Address, usize> = objects
+        .iter()
+        .map(|(address, object)| (*address, Rc::strong_count(object) - 1))
+        .collect();
+    for object in objects.values() {
+        object.trace(&mut |child| {
+            if let Some(count) = external.get_mut(&child) {
+                *count = count.saturating_sub(1);
+            }
+        });
+    }
+
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<Address> = external
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(address, _)| *address)
+        .collect();
+    while let Some(address) = pending.pop() {
+        if !reachable.insert(address) {
+            continue;
+        }
+        objects[&address].trace(&mut |child| {
+            if objects.contains_key(&child) && !reachable.contains(&child) {
+                pending.push(child);
+            }
+        });
+    }
+
+    let mut survivors = vec![];
+    let mut freed = 0;
+    for (address, object) in &objects {
+        if reachable.contains(address) {
+            survivors.push(Rc::downgrade(object));
+        } else {
+            object.clear();
+            freed += 1;
+        }
+    }
+    // Dropping the last references to the garbage frees it.
+    drop(objects);
+
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.objects.extend(survivors);
+        heap.allocated = 0;
+        heap.threshold = MIN_THRESHOLD.max(heap.objects.len());
+        heap.stats.collections += 1;
+        heap.stats.freed += freed;
+    });
+    return freed;
+}
+
+pub fn stats() -> Stats {
+    return HEAP.with(|heap| heap.borrow().stats);
+}
@@ -1,27 +1,95 @@
-use std::{cell::RefCell, rc::Rc};
+use std::rc::Rc;
 
 use crate::{
+    cli::{Backend, Options},
+    error::{Diagnostic, Reporter},
+    gc,
     lexing::lexer::Lexer,
-    parsing::parser::Parser,
-    runtime::{environment::Environment, evaluate::EvaluateStmt},
+    parsing::{expr::LiteralValue, parser::Parser, resolver::Resolver, stmt::Stmt},
+    runtime::{
+        environment::Environment, error::Interrupt, evaluate::EvaluateStmt, native::natives,
+    },
+    vm::{compiler::Compiler, disassemble::disassemble, machine::Vm, object::Function},
 };
 
-pub fn run(source: String) -> Result<(), ()> {
-    let mut lexer = Lexer::new(source);
-    let (tokens, lexer_error_found) = lexer.scan_tokens();
-    let mut parser = Parser::new(tokens.to_vec());
-    let statements = parser.parse();
-    if let Ok(statements) = statements {
-        let environment = Rc::new(RefCell::new(Environment::new(None)));
-        for mut statement in statements {
-            let res = statement.evaluate(environment.clone());
-            if let Err(_) = res {
-                return Err(());
+pub fn run(source: String, options: &Options) -> Result<(), ()> {
+    let reporter = Reporter::new(source.clone(), options);
+
+    let (tokens, lexer_errors) = Lexer::new(source).scan_tokens();
+    for error in &lexer_errors {
+        reporter.report(&Diagnostic::from(error));
+    }
+    let mut parser = Parser::new(tokens);
+    let (statements, parse_errors) = parser.parse();
+    for error in &parse_errors {
+        reporter.report(&Diagnostic::from(error));
+    }
+    if !lexer_errors.is_empty() || !parse_errors.is_empty() {
+        return Err(());
+    }
+    if let Err(resolve_errors) = Resolver::new().resolve(&statements) {
+        for error in &resolve_errors {
+            reporter.report(&Diagnostic::from(error));
+        }
+        return Err(());
+    }
+
+    if options.dump_bytecode {
+        let function = compile(&statements, &reporter)?;
+        print!("{}", disassemble(&function));
+        return Ok(());
+    }
+
+    let result = match options.backend {
+        Backend::Tree => evaluate(&statements, &reporter),
+        Backend::Vm => execute(&statements, &reporter),
+    };
+    if options.gc_stats {
+        let stats = gc::stats();
+        eprintln!(
+            "gc: {} collections, {} objects tracked, {} freed by the collector",
+            stats.collections, stats.tracked, stats.freed
+        );
+    }
+    return result;
+}
+
+/// Runs the program by walking its syntax tree.
+fn evaluate(statements: &[Stmt], reporter: &Reporter) -> Result<(), ()> {
+    let mut globals = Environment::new(None);
+    for native in natives() {
+        globals.define(&Rc::from(native.name), LiteralValue::Native(Rc::new(native)));
+    }
+    let environment = globals.share();
+    for statement in statements {
+        if let Err(interrupt) = statement.evaluate(&environment) {
+            if let Interrupt::Error(error) = interrupt {
+                reporter.report(&Diagnostic::from(&error));
             }
+            return Err(());
         }
-        if lexer_error_found {
+    }
+    return Ok(());
+}
+
+fn compile(statements: &[Stmt], reporter: &Reporter) -> Result<Rc<Function>, ()> {
+    match Compiler::new().compile(statements) {
+        Ok(function) => return Ok(function),
+        Err(errors) => {
+            for error in &errors {
+                reporter.report(&Diagnostic::from(error));
+            }
             return Err(());
         }
     }
-    return Err(());
+}
+
+/// Runs the program by compiling it to bytecode for the virtual machine.
+fn execute(statements: &[Stmt], reporter: &Reporter) -> Result<(), ()> {
+    let function = compile(statements, reporter)?;
+    if let Err(error) = Vm::new().interpret(function) {
+        reporter.report(&Diagnostic::from(&error));
+        return Err(());
+    }
+    return Ok(());
 }
@@ -1,8 +1,22 @@
-use crate::error::report_error;
+use crate::{error::Diagnostic, lexing::span::Span};
 
 pub enum LexerErrorMessage {
     UnterminatedString,
     UnexpectedCharacter,
+    /// The escape sequence as written, from the backslash to where it stopped
+    /// making sense.
+    InvalidEscape(String),
+    /// The radix prefix that no digits followed, such as `0x`.
+    MissingDigits(String),
+    /// The literal up to the exponent marker and sign, such as `1e`.
+    MissingExponent(String),
+    InvalidDigit {
+        digit: char,
+        /// The name of the base, such as `binary`.
+        radix: &'static str,
+    },
+    MisplacedSeparator,
+    UnterminatedComment,
 }
 
 impl std::fmt::Display for LexerErrorMessage {
@@ -10,16 +24,49 @@ impl std::fmt::Display for LexerErrorMessage {
         match self {
             LexerErrorMessage::UnterminatedString => write!(f, "Unterminated string."),
             LexerErrorMessage::UnexpectedCharacter => write!(f, "Unexpected character."),
+            LexerErrorMessage::InvalidEscape(escape) => {
+                write!(f, "Invalid escape sequence '{}'.", escape)
+            }
+            LexerErrorMessage::MissingDigits(prefix) => {
+                write!(f, "Expect digits after '{}'.", prefix)
+            }
+            LexerErrorMessage::MissingExponent(literal) => {
+                write!(f, "Expect exponent digits after '{}'.", literal)
+            }
+            LexerErrorMessage::InvalidDigit { digit, radix } => {
+                write!(f, "Invalid digit '{}' in {} literal.", digit, radix)
+            }
+            LexerErrorMessage::MisplacedSeparator => {
+                write!(f, "Digit separators must sit between two digits.")
+            }
+            LexerErrorMessage::UnterminatedComment => write!(f, "Unterminated block comment."),
+        }
+    }
+}
+
+impl LexerErrorMessage {
+    /// The stable identifier tools can match this error on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexerErrorMessage::UnterminatedString => "E0001",
+            LexerErrorMessage::UnexpectedCharacter => "E0002",
+            LexerErrorMessage::InvalidEscape(_) => "E0003",
+            LexerErrorMessage::MissingDigits(_) => "E0004",
+            LexerErrorMessage::MissingExponent(_) => "E0005",
+            LexerErrorMessage::InvalidDigit { .. } => "E0006",
+            LexerErrorMessage::MisplacedSeparator => "E0007",
+            LexerErrorMessage::UnterminatedComment => "E0009",
         }
     }
 }
 
 pub struct LexerError {
-    pub line: usize,
+    pub span: Span,
     pub message: LexerErrorMessage,
 }
 
-pub fn report_lexer_error(error: LexerError) -> LexerError {
-    report_error(error.line, "", &error.message.to_string());
-    return error;
+impl From<&LexerError> for Diagnostic {
+    fn from(error: &LexerError) -> Self {
+        return Diagnostic::error(error.message.code(), error.message.to_string(), error.span);
+    }
 }
@@ -0,0 +1,25 @@
+use std::{collections::HashSet, rc::Rc};
+
+/// Hands out one shared copy of each distinct string, so that every
+/// occurrence of a name or string literal points at the same allocation.
+/// Comparing two interned strings then usually stops at the pointer check
+/// `Rc`'s equality does before looking at the contents.
+#[derive(Default)]
+pub struct Interner {
+    strings: HashSet<Rc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn intern(&mut self, string: &str) -> Rc<str> {
+        if let Some(interned) = self.strings.get(string) {
+            return interned.clone();
+        }
+        let interned: Rc<str> = Rc::from(string);
+        self.strings.insert(interned.clone());
+        return interned;
+    }
+}
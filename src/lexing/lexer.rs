@@ -1,16 +1,32 @@
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_xid::UnicodeXID;
+
 use super::{
-    error::{report_lexer_error, LexerError, LexerErrorMessage},
+    error::{LexerError, LexerErrorMessage},
+    interner::Interner,
+    span::Span,
     token::{Token, TokenLiteral, TokenType},
 };
 
+/// Turns source text into tokens. Positions into the source are byte offsets,
+/// always on a character boundary, while columns count characters.
 pub struct Lexer {
     pub source: String,
     pub tokens: Vec<Token>,
-    pub error_found: bool,
+    pub errors: Vec<LexerError>,
+    interner: Interner,
+    /// The `${` interpolations the lexer is inside, innermost last, each with
+    /// the number of braces opened in its expression and not yet closed. The
+    /// first `}` once those are closed resumes the interpolation's string.
+    interpolations: Vec<usize>,
 
     start: usize,
     current: usize,
     line: usize,
+    /// Characters consumed so far on the current line.
+    column: usize,
+    start_line: usize,
+    start_column: usize,
 }
 
 impl Lexer {
@@ -18,10 +34,15 @@ impl Lexer {
         return Self {
             source,
             tokens: vec![],
-            error_found: false,
+            errors: vec![],
+            interner: Interner::new(),
+            interpolations: vec![],
             start: 0,
             current: 0,
             line: 1,
+            column: 0,
+            start_line: 1,
+            start_column: 1,
         };
     }
 
@@ -30,103 +51,450 @@ impl Lexer {
     }
 
     fn advance(&mut self) -> char {
-        let char = self.source.chars().nth(self.current).unwrap();
-        self.current += 1;
+        let char = self.peek();
+        self.current += char.len_utf8();
+        self.column += 1;
         return char;
     }
 
+    fn begin_token(&mut self) {
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column + 1;
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 0;
+    }
+
+    /// The span of the lexeme currently being scanned.
+    fn span(&self) -> Span {
+        return Span::new(self.start, self.current, self.start_line, self.start_column);
+    }
+
+    /// An empty span at the character about to be consumed.
+    fn position(&self) -> Span {
+        return Span::new(self.current, self.current, self.line, self.column + 1);
+    }
+
+    fn error(&mut self, message: LexerErrorMessage) {
+        self.error_at(self.span(), message);
+    }
+
+    fn error_at(&mut self, span: Span, message: LexerErrorMessage) {
+        self.errors.push(LexerError { span, message });
+    }
+
     fn add_token(&mut self, token_type: TokenType, literal: TokenLiteral) {
-        let lexeme = &self.source[self.start..self.current];
-        self.tokens.push(Token::new(
-            token_type,
-            String::from(lexeme),
-            literal,
-            self.line,
-        ));
+        let lexeme = self.interner.intern(&self.source[self.start..self.current]);
+        self.tokens.push(Token::new(token_type, lexeme, literal, self.span()));
     }
 
     fn match_next(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        };
-        let Some(next) = self.source.chars().nth(self.current) else {
-                return false;
-            };
-        if next != expected {
+        if self.is_at_end() || self.peek() != expected {
             return false;
         }
-        self.current += 1;
+        self.advance();
         return true;
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        };
-        return self.source.chars().nth(self.current).unwrap();
+        return self.source[self.current..].chars().next().unwrap_or('\0');
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        return self.source.chars().nth(self.current + 1).unwrap();
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        return chars.next().unwrap_or('\0');
     }
 
+    #[allow(clippy::manual_range_contains)]
     fn is_digit(c: char) -> bool {
         return c >= '0' && c <= '9';
     }
 
+    /// Whether `c` can start an identifier, following UAX #31 with `_` added.
     fn is_alpha(c: char) -> bool {
-        return (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_';
+        return c == '_' || c.is_xid_start();
     }
 
     fn is_alphanumeric(c: char) -> bool {
-        return Self::is_digit(c) || Self::is_alpha(c);
+        return c.is_xid_continue();
     }
 
-    fn string(&mut self) {
+    /// Scans a string, or the segment of one that follows an interpolated
+    /// expression, up to its closing quote, where it becomes a `closing`
+    /// token. A segment that ends at a `${` instead becomes an
+    /// `Interpolation` token, and the expression after it is scanned as
+    /// ordinary tokens until its `}`.
+    fn string(&mut self, closing: TokenType) {
+        let mut value = String::new();
         loop {
             if self.peek() == '"' || self.is_at_end() {
                 break;
             }
-            if self.peek() == '\n' {
-                self.line += 1;
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+                let value = self.interner.intern(&value);
+                self.add_token(TokenType::Interpolation, TokenLiteral::String(value));
+                return;
+            }
+            let position = self.position();
+            match self.advance() {
+                '\\' => self.escape(position, &mut value),
+                '\n' => {
+                    self.new_line();
+                    value.push('\n');
+                }
+                c => value.push(c),
             }
-            self.advance();
         }
 
         if self.is_at_end() {
-            report_lexer_error(LexerError {
-                line: self.line,
-                message: LexerErrorMessage::UnterminatedString,
-            });
+            // Interpolations the string was nested in can no longer be closed.
+            self.interpolations.clear();
+            self.error(LexerErrorMessage::UnterminatedString);
             return;
         }
 
         self.advance();
 
-        let value = &self.source[self.start + 1..self.current - 1];
-        self.add_token(TokenType::String, TokenLiteral::String(String::from(value)));
+        let value = self.interner.intern(&value);
+        self.add_token(closing, TokenLiteral::String(value));
+    }
+
+    /// Scans a `"""` string whose first quote has been consumed. Its text
+    /// starts on the line after the opening quotes if nothing else follows
+    /// them, ends with the line before the closing quotes if nothing but
+    /// indentation precedes them, and loses the indentation its lines have
+    /// in common. Escapes work as in other strings, but `${` stands for
+    /// itself.
+    fn multiline_string(&mut self) {
+        self.advance();
+        self.advance();
+        let Some(length) = self.multiline_length() else {
+            self.advance_to(self.source.len());
+            self.error(LexerErrorMessage::UnterminatedString);
+            return;
+        };
+        let end = self.current + length;
+
+        let lines: Vec<&str> = self.source[self.current..end].split('\n').collect();
+        let last = lines.len() - 1;
+        let opening_blank = last > 0 && is_blank(lines[0]);
+        let closing_blank = last > 0 && is_blank(lines[last]);
+        let indent = (1..=last)
+            .filter(|&i| !is_blank(lines[i]) || (i == last && closing_blank))
+            .map(|i| lines[i].chars().take_while(|c| *c == ' ' || *c == '\t').count())
+            .min()
+            .unwrap_or(0);
+        let stop = if closing_blank {
+            end - lines[last].len() - 1
+        } else {
+            end
+        };
+
+        if opening_blank {
+            self.advance_to(self.current + lines[0].len() + 1);
+        }
+        let mut value = String::new();
+        let mut line_start = opening_blank;
+        while self.current < stop {
+            if line_start {
+                let mut stripped = 0;
+                while stripped < indent && matches!(self.peek(), ' ' | '\t') {
+                    self.advance();
+                    stripped += 1;
+                }
+                line_start = false;
+                continue;
+            }
+            let position = self.position();
+            match self.advance() {
+                '\\' => self.escape(position, &mut value),
+                '\n' => {
+                    self.new_line();
+                    value.push('\n');
+                    line_start = true;
+                }
+                c => value.push(c),
+            }
+        }
+        self.advance_to(end + 3);
+
+        let value = self.interner.intern(&value);
+        self.add_token(TokenType::String, TokenLiteral::String(value));
+    }
+
+    /// The length in bytes of the text of the `"""` string being scanned, or
+    /// `None` if it is never closed.
+    fn multiline_length(&self) -> Option<usize> {
+        let rest = &self.source[self.current..];
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' if rest[i..].starts_with("\"\"\"") => return Some(i),
+                _ => {}
+            }
+        }
+        return None;
     }
 
-    fn number(&mut self) {
-        while Self::is_digit(self.peek()) {
+    /// Whether the `r` just consumed starts a raw string: `r"` or `r` and
+    /// some `#`s followed by a quote.
+    fn is_raw_string(&self) -> bool {
+        return self.source[self.current..].trim_start_matches('#').starts_with('"');
+    }
+
+    /// Scans a raw string whose `r` has been consumed. It ends at the first
+    /// quote followed by as many `#`s as preceded the opening one, and
+    /// nothing in between is an escape or an interpolation.
+    fn raw_string(&mut self) {
+        let mut hashes = 0;
+        while self.advance() == '#' {
+            hashes += 1;
+        }
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let Some(length) = self.source[self.current..].find(&closing) else {
+            self.advance_to(self.source.len());
+            self.error(LexerErrorMessage::UnterminatedString);
+            return;
+        };
+        let end = self.current + length;
+
+        let value = self.interner.intern(&self.source[self.current..end]);
+        self.advance_to(end + closing.len());
+        self.add_token(TokenType::String, TokenLiteral::String(value));
+    }
+
+    /// Consumes everything up to the byte offset `end`, counting lines.
+    fn advance_to(&mut self, end: usize) {
+        while self.current < end {
+            if self.advance() == '\n' {
+                self.new_line();
+            }
+        }
+    }
+
+    /// Scans the rest of an escape sequence whose backslash is at `position`
+    /// and adds the character it stands for to `value`. A backslash at the end
+    /// of a line continues the string on the next one, leaving out the `\n` or
+    /// `\r\n` line break and the next line's leading whitespace. Invalid
+    /// escapes are reported and leave nothing in the string.
+    fn escape(&mut self, position: Span, value: &mut String) {
+        if self.is_at_end() {
+            // The string is unterminated, which is reported on its own.
+            return;
+        }
+
+        let cooked = match self.advance() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '$' => Some('$'),
+            '0' => Some('\0'),
+            'x' => {
+                let digits = self.hex_digits(2);
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|code| digits.len() == 2 && *code <= 0x7f)
+                    .and_then(char::from_u32)
+            }
+            'u' if self.match_next('{') => {
+                let digits = self.hex_digits(6);
+                let closed = self.match_next('}');
+                if digits.is_empty() || !closed {
+                    None
+                } else {
+                    u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32)
+                }
+            }
+            '\r' if self.peek() == '\n' => {
+                self.advance();
+                self.continue_line();
+                return;
+            }
+            '\n' => {
+                self.continue_line();
+                return;
+            }
+            _ => None,
+        };
+
+        match cooked {
+            Some(c) => value.push(c),
+            None => {
+                let span = Span {
+                    end: self.current,
+                    ..position
+                };
+                let escape = String::from(&self.source[span.start..span.end]);
+                self.error_at(span, LexerErrorMessage::InvalidEscape(escape));
+            }
+        }
+    }
+
+    /// Starts the line after a backslash at the end of a line in a string,
+    /// dropping its leading spaces and tabs.
+    fn continue_line(&mut self) {
+        self.new_line();
+        while matches!(self.peek(), ' ' | '\t') {
             self.advance();
         }
+    }
+
+    /// Consumes up to `limit` hex digits and returns them.
+    fn hex_digits(&mut self, limit: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < limit && self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+        return digits;
+    }
+
+    /// Scans a number literal whose first digit, `first`, has been consumed:
+    /// a decimal with an optional fraction and exponent, or an integer with a
+    /// `0x`, `0b` or `0o` prefix. Digits may be grouped with `_`.
+    fn number(&mut self, first: char) {
+        let radix = match (first, self.peek()) {
+            ('0', 'x' | 'X') => Some((16, "hexadecimal")),
+            ('0', 'b' | 'B') => Some((2, "binary")),
+            ('0', 'o' | 'O') => Some((8, "octal")),
+            _ => None,
+        };
+        let value = match radix {
+            Some((radix, name)) => self.radix_number(radix, name),
+            None => self.decimal_number(),
+        };
+
+        // Malformed literals have been reported; a placeholder keeps the
+        // parser from reporting them a second time.
+        self.add_token(TokenType::Number, TokenLiteral::Number(value.unwrap_or(0.0)));
+    }
+
+    fn decimal_number(&mut self) -> Option<f64> {
+        let mut text = String::from(&self.source[self.start..self.current]);
+        let mut valid = self.digits(10, &mut text);
 
         if self.peek() == '.' && Self::is_digit(self.peek_next()) {
+            text.push(self.advance());
+            valid &= self.digits(10, &mut text);
+        }
+
+        if matches!(self.peek(), 'e' | 'E') {
+            text.push(self.advance());
+            if matches!(self.peek(), '+' | '-') {
+                text.push(self.advance());
+            }
+            let exponent = text.len();
+            valid &= self.digits(10, &mut text);
+            if valid && text.len() == exponent {
+                let literal = String::from(&self.source[self.start..self.current]);
+                self.error(LexerErrorMessage::MissingExponent(literal));
+                return None;
+            }
+        }
+
+        if !valid {
+            return None;
+        }
+        let value = text.parse().expect("Checked decimal literals are valid floats");
+        return Some(value);
+    }
+
+    fn radix_number(&mut self, radix: u32, name: &'static str) -> Option<f64> {
+        self.advance();
+        let mut digits = String::new();
+        let valid = self.digits(radix, &mut digits);
+
+        let next = self.peek();
+        if next.is_ascii_alphanumeric() {
+            let span = self.position();
             self.advance();
+            let span = Span {
+                end: self.current,
+                ..span
+            };
+            // The rest of the word belongs to the same mistake.
+            while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+                self.advance();
+            }
+            self.error_at(span, LexerErrorMessage::InvalidDigit { digit: next, radix: name });
+            return None;
+        }
+        if !valid {
+            return None;
+        }
+        if digits.is_empty() {
+            let prefix = String::from(&self.source[self.start..self.current]);
+            self.error(LexerErrorMessage::MissingDigits(prefix));
+            return None;
+        }
 
-            while Self::is_digit(self.peek()) {
+        let value = digits.chars().fold(0.0, |value, digit| {
+            let digit = digit.to_digit(radix).unwrap_or(0);
+            return value * radix as f64 + digit as f64;
+        });
+        return Some(value);
+    }
+
+    /// Consumes a run of digits in `radix`, appending them to `digits`
+    /// without the `_`s that separate them. Reports a separator that does
+    /// not sit between two digits and returns whether there was none.
+    fn digits(&mut self, radix: u32, digits: &mut String) -> bool {
+        let mut valid = true;
+        let mut previous = self.source[..self.current].chars().next_back();
+        loop {
+            let c = self.peek();
+            if c.is_digit(radix) {
+                digits.push(self.advance());
+            } else if c == '_' {
+                let span = self.position();
                 self.advance();
+                let after_digit = previous.is_some_and(|previous| previous.is_digit(radix));
+                if valid && !(after_digit && self.peek().is_digit(radix)) {
+                    let span = Span {
+                        end: self.current,
+                        ..span
+                    };
+                    self.error_at(span, LexerErrorMessage::MisplacedSeparator);
+                    valid = false;
+                }
+            } else {
+                return valid;
             }
+            previous = Some(c);
         }
+    }
 
-        self.add_token(
-            TokenType::Number,
-            TokenLiteral::Number(self.source[self.start..self.current].parse().unwrap()),
-        );
+    /// Skips a `/* */` comment whose opening has been consumed, along with
+    /// any comments nested in it.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                let opening = Span {
+                    end: self.start + 2,
+                    ..self.span()
+                };
+                self.error_at(opening, LexerErrorMessage::UnterminatedComment);
+                return;
+            }
+            match self.advance() {
+                '/' if self.match_next('*') => depth += 1,
+                '*' if self.match_next('/') => depth -= 1,
+                '\n' => self.new_line(),
+                _ => {}
+            }
+        }
     }
 
     fn identifier(&mut self) {
@@ -134,10 +502,18 @@ impl Lexer {
             self.advance();
         }
 
+        // Names are compared in NFC, so that differently encoded spellings of
+        // the same text refer to the same variable.
         let word = &self.source[self.start..self.current];
-        let token_type = TokenType::from_keyword(word).unwrap_or(TokenType::Identifier);
+        let lexeme = match is_nfc_quick(word.chars()) {
+            IsNormalized::Yes => self.interner.intern(word),
+            IsNormalized::No | IsNormalized::Maybe => {
+                self.interner.intern(&word.nfc().collect::<String>())
+            }
+        };
+        let token_type = TokenType::from_keyword(&lexeme).unwrap_or(TokenType::Identifier);
 
-        self.add_token(token_type, TokenLiteral::None);
+        self.tokens.push(Token::new(token_type, lexeme, TokenLiteral::None, self.span()));
     }
 
     fn scan_token(&mut self) {
@@ -146,8 +522,22 @@ impl Lexer {
         match c {
             '(' => self.add_token(TokenType::LeftParen, TokenLiteral::None),
             ')' => self.add_token(TokenType::RightParen, TokenLiteral::None),
-            '{' => self.add_token(TokenType::LeftBrace, TokenLiteral::None),
-            '}' => self.add_token(TokenType::RightBrace, TokenLiteral::None),
+            '{' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces += 1;
+                }
+                self.add_token(TokenType::LeftBrace, TokenLiteral::None);
+            }
+            '}' if self.interpolations.last() == Some(&0) => {
+                self.interpolations.pop();
+                self.string(TokenType::InterpolationEnd);
+            }
+            '}' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces -= 1;
+                }
+                self.add_token(TokenType::RightBrace, TokenLiteral::None);
+            }
             ',' => self.add_token(TokenType::Comma, TokenLiteral::None),
             '.' => self.add_token(TokenType::Dot, TokenLiteral::None),
             '-' => self.add_token(TokenType::Minus, TokenLiteral::None),
@@ -194,6 +584,8 @@ impl Lexer {
                         }
                         self.advance();
                     }
+                } else if self.match_next('*') {
+                    self.block_comment();
                 } else {
                     self.add_token(TokenType::Slash, TokenLiteral::None)
                 }
@@ -201,33 +593,40 @@ impl Lexer {
 
             ' ' | '\r' | '\t' => {}
             '\n' => {
-                self.line += 1;
+                self.new_line();
             }
 
-            '"' => self.string(),
+            '"' if self.peek() == '"' && self.peek_next() == '"' => self.multiline_string(),
+            '"' => self.string(TokenType::String),
+            'r' if self.is_raw_string() => self.raw_string(),
             _ if Self::is_digit(c) => {
-                self.number();
+                self.number(c);
             }
             _ if Self::is_alpha(c) => {
                 self.identifier();
             }
             _ => {
-                report_lexer_error(LexerError {
-                    line: self.line,
-                    message: LexerErrorMessage::UnexpectedCharacter,
-                });
+                self.error(LexerErrorMessage::UnexpectedCharacter);
             }
         }
     }
 
-    pub fn scan_tokens(&mut self) -> (&Vec<Token>, bool) {
+    /// Scans the whole source, handing back the tokens along with every
+    /// error found on the way.
+    pub fn scan_tokens(mut self) -> (Vec<Token>, Vec<LexerError>) {
         while !self.is_at_end() {
-            self.start = self.current;
+            self.begin_token();
             self.scan_token();
         }
 
+        self.begin_token();
         self.add_token(TokenType::Eof, TokenLiteral::None);
 
-        return (&self.tokens, self.error_found);
+        return (self.tokens, self.errors);
     }
 }
+
+/// Whether `line` holds nothing but whitespace.
+fn is_blank(line: &str) -> bool {
+    return line.chars().all(|c| matches!(c, ' ' | '\t' | '\r'));
+}
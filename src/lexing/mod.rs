@@ -1,4 +1,6 @@
 
 pub mod token;
 pub mod lexer;
-mod error;
+pub mod span;
+pub mod error;
+pub mod interner;
@@ -0,0 +1,29 @@
+/// A region of the source text. `start` and `end` are byte offsets (end
+/// exclusive); `line` and `column` are the 1-based position of `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Returns a span covering everything from the start of `self` to the end
+    /// of `other`.
+    pub fn to(self, other: Span) -> Span {
+        return Span {
+            end: other.end.max(self.end),
+            ..self
+        };
+    }
+}
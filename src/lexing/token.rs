@@ -1,5 +1,9 @@
+use std::rc::Rc;
+
 use derive_more::Display;
 
+use super::span::Span;
+
 #[derive(Debug, Display, Clone, PartialEq, Eq)]
 pub enum TokenType {
     // Single-character tokens.
@@ -29,6 +33,12 @@ pub enum TokenType {
     Identifier,
     String,
     Number,
+    /// A segment of a string that ends in `${`, before an interpolated
+    /// expression.
+    Interpolation,
+    /// The last segment of an interpolated string, from the `}` closing its
+    /// last expression to the closing quote.
+    InterpolationEnd,
 
     // Keywords.
     And,
@@ -78,29 +88,32 @@ impl TokenType {
 #[derive(Debug, Display, Clone)]
 pub enum TokenLiteral {
     None,
-    String(String),
+    String(Rc<str>),
     Number(f64),
 }
 
+/// A scanned token. The lexeme is shared so that the AST, and the runtime
+/// structures built from it, can hold on to names without copying them.
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
-    pub lexeme: String,
+    pub lexeme: Rc<str>,
     pub literal: TokenLiteral,
-    pub line: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: TokenLiteral, line: usize) -> Self {
+    pub fn new(token_type: TokenType, lexeme: Rc<str>, literal: TokenLiteral, span: Span) -> Self {
         Self {
             token_type,
             lexeme,
             literal,
-            line,
+            span,
         }
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for Token {
     fn to_string(&self) -> String {
         format!("{0} {1} {2}", self.token_type, self.lexeme, self.literal)
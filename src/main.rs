@@ -1,29 +1,56 @@
-use std::{env, fs, process};
+use std::{env, fs, process, thread};
 
+use cli::{Options, USAGE};
 use interpreter::run;
 use prompt::run_prompt;
+use runtime::callable::STACK_SIZE;
 
+mod cli;
 mod error;
+mod gc;
 mod interpreter;
 mod prompt;
 mod lexing;
 mod parsing;
 mod runtime;
+mod vm;
 
 fn main() {
-    let mut args = env::args();
-    args.next();
-
-    if args.len() > 2 {
-        print!("Usage: rloxi [script]");
-        process::exit(1);
+    // Programs run on a thread of their own to get a stack of a known size.
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run_cli)
+        .expect("Failed to start the interpreter thread");
+    // A panic has already been reported by the thread itself.
+    if interpreter.join().is_err() {
+        process::exit(101);
     }
-    if let Some(path) = args.next() {
+}
+
+fn run_cli() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(64);
+        }
+    };
+
+    if let Some(code) = &options.explain {
+        match error::explain::explain(code) {
+            Some(explanation) => print!("{}", explanation),
+            None => {
+                eprintln!("'{}' is not a known error code.", code);
+                process::exit(64);
+            }
+        }
+    } else if let Some(path) = &options.script {
         let script = fs::read_to_string(path).expect("Failed to read file");
-        if let Err(_err) = run(script) {
+        if let Err(_err) = run(script, &options) {
             process::exit(1);
         }
     } else {
-        run_prompt();
+        run_prompt(&options);
     }
 }
@@ -1,4 +1,6 @@
-use super::expr::{Expr, LiteralExpr, LiteralValue, UnaryExpr, BinaryExpr, GroupingExpr, VariableExpr};
+#![allow(dead_code)]
+
+use super::expr::{Expr, LiteralExpr, UnaryExpr, BinaryExpr, GroupingExpr, VariableExpr, CallExpr, GetExpr, SetExpr, InterpolationExpr, LogicalExpr};
 
 trait ExprPrint {
     fn print(&self) -> String;
@@ -25,20 +27,20 @@ impl ExprPrint for Expr {
             Expr::BinaryExpr(expr) => expr.print(),
             Expr::VariableExpr(expr) => expr.print(),
             Expr::AssignExpr(_) => todo!(),
-            Expr::LogicalExpr(_) => todo!(),
+            Expr::LogicalExpr(expr) => expr.print(),
+            Expr::CallExpr(expr) => expr.print(),
+            Expr::GetExpr(expr) => expr.print(),
+            Expr::SetExpr(expr) => expr.print(),
+            Expr::ThisExpr(_) => String::from("this"),
+            Expr::SuperExpr(expr) => format!("super.{}", expr.method.lexeme),
+            Expr::InterpolationExpr(expr) => expr.print(),
         }
     }
 }
 
 impl ExprPrint for LiteralExpr {
     fn print(&self) -> String {
-        let val = match &self.value {
-            LiteralValue::String(text) => text.clone(),
-            LiteralValue::Number(number) => number.to_string(),
-            LiteralValue::Bool(bool) => bool.to_string(),
-            LiteralValue::None => "nil".to_string(),
-        };
-        return val;
+        return self.value.to_string();
     }
 }
 
@@ -57,6 +59,15 @@ impl ExprPrint for BinaryExpr {
     }
 }
 
+impl ExprPrint for LogicalExpr {
+    fn print(&self) -> String {
+        return parenthesize(
+            self.operator.lexeme.to_string(),
+            vec![&self.left, &self.right],
+        );
+    }
+}
+
 impl ExprPrint for GroupingExpr {
     fn print(&self) -> String {
         return parenthesize("group".to_string(), vec![&self.expression]);
@@ -65,6 +76,35 @@ impl ExprPrint for GroupingExpr {
 
 impl ExprPrint for VariableExpr {
     fn print(&self) -> String {
-        return String::from(&self.name.lexeme);
+        return self.name.lexeme.to_string();
+    }
+}
+
+impl ExprPrint for CallExpr {
+    fn print(&self) -> String {
+        let mut exprs = vec![&self.callee];
+        exprs.extend(self.arguments.iter());
+        return parenthesize("call".to_string(), exprs);
+    }
+}
+
+impl ExprPrint for GetExpr {
+    fn print(&self) -> String {
+        return parenthesize(format!(".{}", self.name.lexeme), vec![&self.object]);
+    }
+}
+
+impl ExprPrint for SetExpr {
+    fn print(&self) -> String {
+        return parenthesize(
+            format!("={}", self.name.lexeme),
+            vec![&self.object, &self.value],
+        );
+    }
+}
+
+impl ExprPrint for InterpolationExpr {
+    fn print(&self) -> String {
+        return parenthesize("interpolate".to_string(), self.expressions.iter().collect());
     }
 }
@@ -1,8 +1,23 @@
 use crate::{
-    error::report_error,
-    lexing::token::{Token, TokenType},
+    error::Diagnostic,
+    lexing::token::Token,
 };
 
+#[derive(Clone, Copy, Debug)]
+pub enum FunctionKind {
+    Function,
+    Method,
+}
+
+impl std::fmt::Display for FunctionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FunctionKind::Function => write!(f, "function"),
+            FunctionKind::Method => write!(f, "method"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum ExpectAfter {
     While,
@@ -14,6 +29,10 @@ pub enum ExpectAfter {
     Value,
     Declaration,
     LoopCondition,
+    Arguments,
+    Parameters,
+    ReturnValue,
+    Name(FunctionKind),
 }
 
 impl std::fmt::Display for ExpectAfter {
@@ -27,7 +46,11 @@ impl std::fmt::Display for ExpectAfter {
             ExpectAfter::Value => write!(f, "value"),
             ExpectAfter::Declaration => write!(f, "declaration"),
             ExpectAfter::LoopCondition => write!(f, "loop condition"),
-            ExpectAfter::ForClauses => write!(f, "for clauses")
+            ExpectAfter::ForClauses => write!(f, "for clauses"),
+            ExpectAfter::Arguments => write!(f, "arguments"),
+            ExpectAfter::Parameters => write!(f, "parameters"),
+            ExpectAfter::ReturnValue => write!(f, "return value"),
+            ExpectAfter::Name(kind) => write!(f, "{} name", kind),
         }
     }
 }
@@ -41,6 +64,26 @@ pub enum ParseErrorMessage {
     ExpectBraceAfterBlock,
     ExpectVariableName,
     InvalidAssignmentTarget,
+    ExpectName(FunctionKind),
+    ExpectParameterName,
+    ExpectBraceBeforeBody(FunctionKind),
+    TooManyArguments,
+    TooManyParameters,
+    ExpectClassName,
+    ExpectBraceBeforeClassBody,
+    ExpectBraceAfterClassBody,
+    ExpectPropertyName,
+    ExpectSuperclassName,
+    ExpectDotAfterSuper,
+    ExpectSuperclassMethodName,
+    ReadLocalInOwnInitializer,
+    AlreadyDeclared,
+    ReturnFromTopLevel,
+    ReturnValueFromInitializer,
+    ThisOutsideClass,
+    SuperOutsideClass,
+    SuperWithoutSuperclass,
+    ExpectBraceAfterInterpolation,
 }
 
 impl std::fmt::Display for ParseErrorMessage {
@@ -53,6 +96,89 @@ impl std::fmt::Display for ParseErrorMessage {
             ParseErrorMessage::ExpectVariableName => write!(f, "Expect variable name."),
             ParseErrorMessage::InvalidAssignmentTarget => write!(f, "Invalid assignment target."),
             ParseErrorMessage::ExpectBraceAfterBlock => write!(f, "Expect '}}' after block."),
+            ParseErrorMessage::ExpectName(kind) => write!(f, "Expect {} name.", kind),
+            ParseErrorMessage::ExpectParameterName => write!(f, "Expect parameter name."),
+            ParseErrorMessage::ExpectBraceBeforeBody(kind) => {
+                write!(f, "Expect '{{' before {} body.", kind)
+            }
+            ParseErrorMessage::TooManyArguments => {
+                write!(f, "Can't have more than 255 arguments.")
+            }
+            ParseErrorMessage::TooManyParameters => {
+                write!(f, "Can't have more than 255 parameters.")
+            }
+            ParseErrorMessage::ExpectClassName => write!(f, "Expect class name."),
+            ParseErrorMessage::ExpectBraceBeforeClassBody => {
+                write!(f, "Expect '{{' before class body.")
+            }
+            ParseErrorMessage::ExpectBraceAfterClassBody => {
+                write!(f, "Expect '}}' after class body.")
+            }
+            ParseErrorMessage::ExpectPropertyName => {
+                write!(f, "Expect property name after '.'.")
+            }
+            ParseErrorMessage::ExpectSuperclassName => write!(f, "Expect superclass name."),
+            ParseErrorMessage::ExpectDotAfterSuper => write!(f, "Expect '.' after 'super'."),
+            ParseErrorMessage::ExpectSuperclassMethodName => {
+                write!(f, "Expect superclass method name.")
+            }
+            ParseErrorMessage::ReadLocalInOwnInitializer => {
+                write!(f, "Can't read local variable in its own initializer.")
+            }
+            ParseErrorMessage::AlreadyDeclared => {
+                write!(f, "Already a variable with this name in this scope.")
+            }
+            ParseErrorMessage::ReturnFromTopLevel => write!(f, "Can't return from top-level code."),
+            ParseErrorMessage::ReturnValueFromInitializer => {
+                write!(f, "Can't return a value from an initializer.")
+            }
+            ParseErrorMessage::ThisOutsideClass => {
+                write!(f, "Can't use 'this' outside of a class.")
+            }
+            ParseErrorMessage::SuperOutsideClass => {
+                write!(f, "Can't use 'super' outside of a class.")
+            }
+            ParseErrorMessage::SuperWithoutSuperclass => {
+                write!(f, "Can't use 'super' in a class with no superclass.")
+            }
+            ParseErrorMessage::ExpectBraceAfterInterpolation => {
+                write!(f, "Expect '}}' after interpolated expression.")
+            }
+        }
+    }
+}
+
+impl ParseErrorMessage {
+    /// The stable identifier tools can match this error on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseErrorMessage::ExpectLeftParen(_) => "E0101",
+            ParseErrorMessage::ExpectRightParen(_) => "E0102",
+            ParseErrorMessage::ExpectExpression => "E0103",
+            ParseErrorMessage::ExpectSemicolon(_) => "E0104",
+            ParseErrorMessage::ExpectBraceAfterBlock => "E0105",
+            ParseErrorMessage::ExpectVariableName => "E0106",
+            ParseErrorMessage::InvalidAssignmentTarget => "E0107",
+            ParseErrorMessage::ExpectName(_) => "E0108",
+            ParseErrorMessage::ExpectParameterName => "E0109",
+            ParseErrorMessage::ExpectBraceBeforeBody(_) => "E0110",
+            ParseErrorMessage::TooManyArguments => "E0111",
+            ParseErrorMessage::TooManyParameters => "E0112",
+            ParseErrorMessage::ExpectClassName => "E0113",
+            ParseErrorMessage::ExpectBraceBeforeClassBody => "E0114",
+            ParseErrorMessage::ExpectBraceAfterClassBody => "E0115",
+            ParseErrorMessage::ExpectPropertyName => "E0116",
+            ParseErrorMessage::ExpectSuperclassName => "E0117",
+            ParseErrorMessage::ExpectDotAfterSuper => "E0118",
+            ParseErrorMessage::ExpectSuperclassMethodName => "E0119",
+            ParseErrorMessage::ReadLocalInOwnInitializer => "E0120",
+            ParseErrorMessage::AlreadyDeclared => "E0121",
+            ParseErrorMessage::ReturnFromTopLevel => "E0122",
+            ParseErrorMessage::ReturnValueFromInitializer => "E0123",
+            ParseErrorMessage::ThisOutsideClass => "E0124",
+            ParseErrorMessage::SuperOutsideClass => "E0125",
+            ParseErrorMessage::SuperWithoutSuperclass => "E0126",
+            ParseErrorMessage::ExpectBraceAfterInterpolation => "E0127",
         }
     }
 }
@@ -63,14 +189,12 @@ pub struct ParseError {
     pub message: ParseErrorMessage,
 }
 
-pub fn report_parser_error(error: ParseError) -> ParseError {
-    if error.token.token_type == TokenType::Eof {
-        report_error(error.token.line, " at end", &error.message.to_string());
-    } else {
-        let mut location = String::from(" at '");
-        location.push_str(&error.token.lexeme);
-        location.push('\'');
-        report_error(error.token.line, &location, &error.message.to_string());
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        return Diagnostic::error(
+            error.message.code(),
+            error.message.to_string(),
+            error.token.span,
+        );
     }
-    return error;
 }
@@ -1,10 +1,22 @@
-use crate::lexing::token::Token;
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    gc::{self, Address},
+    lexing::{span::Span, token::Token},
+    runtime::{
+        class::LoxClass, function::LoxFunction, instance::LoxInstance, native::NativeFunction,
+    },
+};
 
 #[derive(Clone, Debug)]
 pub enum LiteralValue {
-    String(String),
+    String(Rc<str>),
     Number(f64),
     Bool(bool),
+    Function(Rc<LoxFunction>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<LoxInstance>),
     None,
 }
 
@@ -14,13 +26,65 @@ impl PartialEq for LiteralValue {
             (Self::String(l0), Self::String(r0)) => l0 == r0,
             (Self::Number(l0), Self::Number(r0)) => l0 == r0,
             (Self::Bool(l0), Self::Bool(r0)) => l0 == r0,
+            (Self::Function(l0), Self::Function(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Native(l0), Self::Native(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Class(l0), Self::Class(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Instance(l0), Self::Instance(r0)) => Rc::ptr_eq(l0, r0),
             (Self::None, Self::None) => true,
             _ => false,
         }
     }
 }
 
+impl LiteralValue {
+    /// The name of the value's type as it appears in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LiteralValue::String(_) => "a string",
+            LiteralValue::Number(_) => "a number",
+            LiteralValue::Bool(_) => "a boolean",
+            LiteralValue::Function(_) | LiteralValue::Native(_) => "a function",
+            LiteralValue::Class(_) => "a class",
+            LiteralValue::Instance(_) => "an instance",
+            LiteralValue::None => "nil",
+        }
+    }
+
+    /// Visits the object this value refers to, if the collector tracks it.
+    pub fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        match self {
+            LiteralValue::Function(function) => visit(gc::address(function)),
+            LiteralValue::Class(class) => visit(gc::address(class)),
+            LiteralValue::Instance(instance) => visit(gc::address(instance)),
+            LiteralValue::String(_)
+            | LiteralValue::Number(_)
+            | LiteralValue::Bool(_)
+            | LiteralValue::Native(_)
+            | LiteralValue::None => {}
+        }
+    }
+}
+
+/// Shows values the way Lox programs see them, in `print`, interpolation,
+/// `str()` and string concatenation.
+impl std::fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiteralValue::String(string) => write!(f, "{}", string),
+            // Integral numbers have no fractional part to show.
+            LiteralValue::Number(number) => write!(f, "{}", number),
+            LiteralValue::Bool(bool) => write!(f, "{}", bool),
+            LiteralValue::Function(function) => write!(f, "{:?}", function),
+            LiteralValue::Native(native) => write!(f, "{:?}", native),
+            LiteralValue::Class(class) => write!(f, "{:?}", class),
+            LiteralValue::Instance(instance) => write!(f, "{:?}", instance),
+            LiteralValue::None => write!(f, "nil"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Expr {
     AssignExpr(Box<AssignExpr>),
     UnaryExpr(Box<UnaryExpr>),
@@ -29,23 +93,63 @@ pub enum Expr {
     BinaryExpr(Box<BinaryExpr>),
     VariableExpr(VariableExpr),
     LogicalExpr(Box<LogicalExpr>),
+    CallExpr(Box<CallExpr>),
+    GetExpr(Box<GetExpr>),
+    SetExpr(Box<SetExpr>),
+    ThisExpr(ThisExpr),
+    SuperExpr(SuperExpr),
+    InterpolationExpr(InterpolationExpr),
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::AssignExpr(expr) => expr.span,
+            Expr::UnaryExpr(expr) => expr.span,
+            Expr::LiteralExpr(expr) => expr.span,
+            Expr::GroupingExpr(expr) => expr.span,
+            Expr::BinaryExpr(expr) => expr.span,
+            Expr::VariableExpr(expr) => expr.span,
+            Expr::LogicalExpr(expr) => expr.span,
+            Expr::CallExpr(expr) => expr.span,
+            Expr::GetExpr(expr) => expr.span,
+            Expr::SetExpr(expr) => expr.span,
+            Expr::ThisExpr(expr) => expr.span,
+            Expr::SuperExpr(expr) => expr.span,
+            Expr::InterpolationExpr(expr) => expr.span,
+        }
+    }
+}
+
+/// Where the resolver found a local variable: `depth` scopes out from its use,
+/// at position `slot` among that scope's variables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalSlot {
+    pub depth: usize,
+    pub slot: usize,
 }
 
+/// The `local` fields on variable-like expressions are filled in by the
+/// resolver. `None` means the name is a global.
 #[derive(Clone, Debug)]
 pub struct AssignExpr {
     pub name: Token,
     pub value: Expr,
+    pub local: Cell<Option<LocalSlot>>,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
 pub struct LiteralExpr {
     pub value: LiteralValue,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
 pub struct UnaryExpr {
     pub operator: Token,
     pub right: Expr,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
@@ -53,16 +157,20 @@ pub struct BinaryExpr {
     pub left: Expr,
     pub operator: Token,
     pub right: Expr,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
 pub struct GroupingExpr {
     pub expression: Expr,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
 pub struct VariableExpr {
     pub name: Token,
+    pub local: Cell<Option<LocalSlot>>,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
@@ -70,4 +178,52 @@ pub struct LogicalExpr {
     pub left: Expr,
     pub operator: Token,
     pub right: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct CallExpr {
+    pub callee: Expr,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct GetExpr {
+    pub object: Expr,
+    pub name: Token,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct SetExpr {
+    pub object: Expr,
+    pub name: Token,
+    pub value: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct ThisExpr {
+    pub keyword: Token,
+    pub local: Cell<Option<LocalSlot>>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct SuperExpr {
+    pub keyword: Token,
+    pub method: Token,
+    pub local: Cell<Option<LocalSlot>>,
+    pub span: Span,
+}
+
+/// A string with expressions spliced into it. The literal `segments` surround
+/// the `expressions`, so there is always one more segment than expressions.
+#[derive(Clone, Debug)]
+pub struct InterpolationExpr {
+    pub segments: Vec<Rc<str>>,
+    pub expressions: Vec<Expr>,
+    pub span: Span,
 }
@@ -2,4 +2,5 @@ pub mod expr;
 pub mod parser;
 pub mod error;
 pub mod stmt;
+pub mod resolver;
 mod debug;
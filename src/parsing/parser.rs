@@ -1,54 +1,169 @@
+use std::{cell::Cell, rc::Rc};
+
 use crate::{
-    lexing::token::{Token, TokenLiteral, TokenType},
+    lexing::{
+        span::Span,
+        token::{Token, TokenLiteral, TokenType},
+    },
     parsing::expr::{GroupingExpr, LiteralExpr},
 };
 
 use super::{
-    error::{report_parser_error, ExpectAfter, ParseError, ParseErrorMessage},
-    expr::{AssignExpr, BinaryExpr, Expr, LiteralValue, LogicalExpr, UnaryExpr, VariableExpr},
-    stmt::{BlockStmt, ExpressionStmt, IfStmt, PrintStmt, Stmt, VarStmt, WhileStmt},
+    error::{ExpectAfter, FunctionKind, ParseError, ParseErrorMessage},
+    expr::{
+        AssignExpr, BinaryExpr, CallExpr, Expr, GetExpr, InterpolationExpr, LiteralValue,
+        LogicalExpr, SetExpr, SuperExpr, ThisExpr, UnaryExpr, VariableExpr,
+    },
+    stmt::{
+        BlockStmt, ClassStmt, ExpressionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt, Stmt,
+        VarStmt, WhileStmt,
+    },
 };
 
+const MAX_ARGUMENTS: usize = 255;
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            errors: vec![],
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, ParseError> {
+    /// Parses the whole token stream, recovering after each error so that every
+    /// problem in the script is reported in a single pass. The statements are
+    /// only safe to run when the returned error list is empty.
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<ParseError>) {
         let mut statements = vec![];
         while !self.is_at_end() {
-            statements.push(self.declaration()?);
+            if let Some(statement) = self.recovering_declaration() {
+                statements.push(statement);
+            }
         }
 
-        return Ok(statements);
+        return (statements, std::mem::take(&mut self.errors));
     }
 
-    fn declaration(&mut self) -> Result<Stmt, ParseError> {
-        if self.type_match(vec![TokenType::Var]) {
-            let result = self.var_declaration();
-            match result {
-                Ok(statement) => {
-                    return Ok(statement);
-                }
-                Err(err) => {
-                    self.synchronize();
-                    return Err(err);
-                }
+    fn recovering_declaration(&mut self) -> Option<Stmt> {
+        match self.declaration() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                self.errors.push(error);
+                None
             }
         }
-        let result = self.statement();
-        if let Err(_) = result {
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        let result = if self.type_match(vec![TokenType::Class]) {
+            self.class_declaration()
+        } else if self.type_match(vec![TokenType::Fun]) {
+            self.function(FunctionKind::Function).map(Stmt::FunctionStmt)
+        } else if self.type_match(vec![TokenType::Var]) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        };
+        if result.is_err() {
             self.synchronize();
         }
         return result;
     }
 
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        let name = self.consume(TokenType::Identifier, ParseErrorMessage::ExpectClassName)?;
+
+        let mut superclass = None;
+        if self.type_match(vec![TokenType::Less]) {
+            self.consume(
+                TokenType::Identifier,
+                ParseErrorMessage::ExpectSuperclassName,
+            )?;
+            let name = self.previous();
+            superclass = Some(VariableExpr {
+                span: name.span,
+                name,
+                local: Cell::new(None),
+            });
+        }
+
+        self.consume(
+            TokenType::LeftBrace,
+            ParseErrorMessage::ExpectBraceBeforeClassBody,
+        )?;
+
+        let mut methods = vec![];
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function(FunctionKind::Method)?);
+        }
+
+        self.consume(
+            TokenType::RightBrace,
+            ParseErrorMessage::ExpectBraceAfterClassBody,
+        )?;
+
+        return Ok(Stmt::ClassStmt(ClassStmt {
+            name,
+            superclass,
+            methods,
+            span: self.span_from(start),
+        }));
+    }
+
+    fn function(&mut self, kind: FunctionKind) -> Result<Rc<FunctionStmt>, ParseError> {
+        let name = self.consume(TokenType::Identifier, ParseErrorMessage::ExpectName(kind))?;
+        self.consume(
+            TokenType::LeftParen,
+            ParseErrorMessage::ExpectLeftParen(ExpectAfter::Name(kind)),
+        )?;
+
+        let mut params = vec![];
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.errors.push(ParseError {
+                        token: self.peek().clone(),
+                        message: ParseErrorMessage::TooManyParameters,
+                    });
+                }
+                params.push(self.consume(
+                    TokenType::Identifier,
+                    ParseErrorMessage::ExpectParameterName,
+                )?);
+                if !self.type_match(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            TokenType::RightParen,
+            ParseErrorMessage::ExpectRightParen(ExpectAfter::Parameters),
+        )?;
+
+        self.consume(
+            TokenType::LeftBrace,
+            ParseErrorMessage::ExpectBraceBeforeBody(kind),
+        )?;
+        let body = self.block()?;
+
+        return Ok(Rc::new(FunctionStmt {
+            span: self.span_from(name.span),
+            name,
+            params,
+            body,
+        }));
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
         let name = self.consume(TokenType::Identifier, ParseErrorMessage::ExpectVariableName)?;
 
         let mut initializer: Option<Expr> = None;
@@ -60,7 +175,11 @@ impl Parser {
             TokenType::Semicolon,
             ParseErrorMessage::ExpectSemicolon(ExpectAfter::Declaration),
         )?;
-        return Ok(Stmt::VarStmt(VarStmt { name, initializer }));
+        return Ok(Stmt::VarStmt(VarStmt {
+            name,
+            initializer,
+            span: self.span_from(start),
+        }));
     }
 
     fn statement(&mut self) -> Result<Stmt, ParseError> {
@@ -73,18 +192,26 @@ impl Parser {
         if self.type_match(vec![TokenType::Print]) {
             return self.print_statement();
         }
+        if self.type_match(vec![TokenType::Return]) {
+            return self.return_statement();
+        }
         if self.type_match(vec![TokenType::While]) {
             return self.while_statement();
         }
         if self.type_match(vec![TokenType::LeftBrace]) {
+            let start = self.previous().span;
             let statements = self.block()?;
-            return Ok(Stmt::BlockStmt(BlockStmt { statements }));
+            return Ok(Stmt::BlockStmt(BlockStmt {
+                statements,
+                span: self.span_from(start),
+            }));
         }
 
         return self.expression_statement();
     }
 
     fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
         self.consume(
             TokenType::LeftParen,
             ParseErrorMessage::ExpectLeftParen(ExpectAfter::For),
@@ -118,28 +245,40 @@ impl Parser {
             ParseErrorMessage::ExpectRightParen(ExpectAfter::ForClauses),
         )?;
         let mut body = self.statement()?;
+        let span = self.span_from(start);
         if let Some(increment) = increment {
             let increment = Stmt::ExpressionStmt(ExpressionStmt {
+                span: increment.span(),
                 expression: increment,
             });
             body = Stmt::BlockStmt(BlockStmt {
+                span: body.span(),
                 statements: vec![body, increment],
             });
         }
 
         let condition = condition.unwrap_or(Expr::LiteralExpr(LiteralExpr {
             value: LiteralValue::Bool(true),
+            span: start,
+        }));
+        body = Stmt::WhileStmt(Box::new(WhileStmt {
+            condition,
+            body,
+            span,
         }));
-        body = Stmt::WhileStmt(Box::new(WhileStmt { condition, body }));
 
         if let Some(initializer) = initializer {
-            body = Stmt::BlockStmt(BlockStmt { statements: vec![initializer, body] });
+            body = Stmt::BlockStmt(BlockStmt {
+                statements: vec![initializer, body],
+                span,
+            });
         }
 
         return Ok(body);
     }
 
     fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
         self.consume(
             TokenType::LeftParen,
             ParseErrorMessage::ExpectLeftParen(ExpectAfter::While),
@@ -151,10 +290,15 @@ impl Parser {
         )?;
         let body = self.statement()?;
 
-        return Ok(Stmt::WhileStmt(Box::new(WhileStmt { condition, body })));
+        return Ok(Stmt::WhileStmt(Box::new(WhileStmt {
+            condition,
+            body,
+            span: self.span_from(start),
+        })));
     }
 
     fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
         self.consume(
             TokenType::LeftParen,
             ParseErrorMessage::ExpectLeftParen(ExpectAfter::If),
@@ -175,6 +319,7 @@ impl Parser {
             condition,
             then_branch,
             else_branch,
+            span: self.span_from(start),
         })));
     }
 
@@ -182,7 +327,9 @@ impl Parser {
         let mut statements = vec![];
 
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            statements.push(self.declaration()?);
+            if let Some(statement) = self.recovering_declaration() {
+                statements.push(statement);
+            }
         }
 
         self.consume(
@@ -193,12 +340,34 @@ impl Parser {
     }
 
     fn print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
         let value = self.expression()?;
         self.consume(
             TokenType::Semicolon,
             ParseErrorMessage::ExpectSemicolon(ExpectAfter::Value),
         )?;
-        return Ok(Stmt::PrintStmt(PrintStmt { expression: value }));
+        return Ok(Stmt::PrintStmt(PrintStmt {
+            expression: value,
+            span: self.span_from(start),
+        }));
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let mut value = None;
+        if !self.check(TokenType::Semicolon) {
+            value = Some(self.expression()?);
+        }
+
+        self.consume(
+            TokenType::Semicolon,
+            ParseErrorMessage::ExpectSemicolon(ExpectAfter::ReturnValue),
+        )?;
+        return Ok(Stmt::ReturnStmt(ReturnStmt {
+            span: self.span_from(keyword.span),
+            keyword,
+            value,
+        }));
     }
 
     fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
@@ -207,7 +376,10 @@ impl Parser {
             TokenType::Semicolon,
             ParseErrorMessage::ExpectSemicolon(ExpectAfter::Expression),
         )?;
-        return Ok(Stmt::ExpressionStmt(ExpressionStmt { expression: expr }));
+        return Ok(Stmt::ExpressionStmt(ExpressionStmt {
+            span: self.span_from(expr.span()),
+            expression: expr,
+        }));
     }
 
     fn expression(&mut self) -> Result<Expr, ParseError> {
@@ -221,12 +393,26 @@ impl Parser {
             let equals = self.previous();
             let value = self.assignment()?;
 
+            let span = expr.span().to(value.span());
             if let Expr::VariableExpr(expr) = expr {
                 let name = expr.name;
-                return Ok(Expr::AssignExpr(Box::new(AssignExpr { name, value })));
+                return Ok(Expr::AssignExpr(Box::new(AssignExpr {
+                    name,
+                    value,
+                    local: Cell::new(None),
+                    span,
+                })));
+            }
+            if let Expr::GetExpr(get) = expr {
+                return Ok(Expr::SetExpr(Box::new(SetExpr {
+                    object: get.object,
+                    name: get.name,
+                    value,
+                    span,
+                })));
             }
 
-            report_parser_error(ParseError {
+            self.errors.push(ParseError {
                 token: equals,
                 message: ParseErrorMessage::InvalidAssignmentTarget,
             });
@@ -242,6 +428,7 @@ impl Parser {
             let operator = self.previous();
             let right = self.and()?;
             expr = Expr::LogicalExpr(Box::new(LogicalExpr {
+                span: expr.span().to(right.span()),
                 left: expr,
                 operator,
                 right,
@@ -258,6 +445,7 @@ impl Parser {
             let operator = self.previous();
             let right = self.equality()?;
             expr = Expr::LogicalExpr(Box::new(LogicalExpr {
+                span: expr.span().to(right.span()),
                 left: expr,
                 operator,
                 right,
@@ -274,6 +462,7 @@ impl Parser {
             let operator = self.previous();
             let right = self.comparison()?;
             expr = Expr::BinaryExpr(Box::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: expr,
                 operator,
                 right,
@@ -295,6 +484,7 @@ impl Parser {
             let operator = self.previous();
             let right = self.term()?;
             expr = Expr::BinaryExpr(Box::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: expr,
                 operator,
                 right,
@@ -310,6 +500,7 @@ impl Parser {
             let operator = self.previous();
             let right = self.factor()?;
             expr = Expr::BinaryExpr(Box::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: expr,
                 operator,
                 right,
@@ -326,6 +517,7 @@ impl Parser {
             let operator = self.previous();
             let right = self.unary()?;
             expr = Expr::BinaryExpr(Box::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: expr,
                 operator,
                 right,
@@ -339,26 +531,87 @@ impl Parser {
         if self.type_match(vec![TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous();
             let right = self.unary()?;
-            return Ok(Expr::UnaryExpr(Box::new(UnaryExpr { operator, right })));
+            return Ok(Expr::UnaryExpr(Box::new(UnaryExpr {
+                span: operator.span.to(right.span()),
+                operator,
+                right,
+            })));
+        }
+
+        return self.call();
+    }
+
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.type_match(vec![TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.type_match(vec![TokenType::Dot]) {
+                let name = self.consume(
+                    TokenType::Identifier,
+                    ParseErrorMessage::ExpectPropertyName,
+                )?;
+                expr = Expr::GetExpr(Box::new(GetExpr {
+                    span: expr.span().to(name.span),
+                    object: expr,
+                    name,
+                }));
+            } else {
+                break;
+            }
+        }
+
+        return Ok(expr);
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments = vec![];
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    self.errors.push(ParseError {
+                        token: self.peek().clone(),
+                        message: ParseErrorMessage::TooManyArguments,
+                    });
+                }
+                arguments.push(self.expression()?);
+                if !self.type_match(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
         }
 
-        return Ok(self.primary()?);
+        let paren = self.consume(
+            TokenType::RightParen,
+            ParseErrorMessage::ExpectRightParen(ExpectAfter::Arguments),
+        )?;
+
+        return Ok(Expr::CallExpr(Box::new(CallExpr {
+            span: callee.span().to(paren.span),
+            callee,
+            paren,
+            arguments,
+        })));
     }
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
         if self.type_match(vec![TokenType::False]) {
             return Ok(Expr::LiteralExpr(LiteralExpr {
                 value: LiteralValue::Bool(false),
+                span: self.previous().span,
             }));
         }
         if self.type_match(vec![TokenType::True]) {
             return Ok(Expr::LiteralExpr(LiteralExpr {
                 value: LiteralValue::Bool(true),
+                span: self.previous().span,
             }));
         }
         if self.type_match(vec![TokenType::Nil]) {
             return Ok(Expr::LiteralExpr(LiteralExpr {
                 value: LiteralValue::None,
+                span: self.previous().span,
             }));
         }
 
@@ -371,37 +624,104 @@ impl Parser {
                 TokenLiteral::String(text) => {
                     return Ok(Expr::LiteralExpr(LiteralExpr {
                         value: LiteralValue::String(text),
+                        span: token.span,
                     }))
                 }
                 TokenLiteral::Number(number) => {
                     return Ok(Expr::LiteralExpr(LiteralExpr {
                         value: LiteralValue::Number(number),
+                        span: token.span,
                     }))
                 }
             }
         }
 
+        if self.type_match(vec![TokenType::Interpolation]) {
+            return self.interpolation();
+        }
+
+        if self.type_match(vec![TokenType::Super]) {
+            let keyword = self.previous();
+            self.consume(TokenType::Dot, ParseErrorMessage::ExpectDotAfterSuper)?;
+            let method = self.consume(
+                TokenType::Identifier,
+                ParseErrorMessage::ExpectSuperclassMethodName,
+            )?;
+            return Ok(Expr::SuperExpr(SuperExpr {
+                span: keyword.span.to(method.span),
+                keyword,
+                method,
+                local: Cell::new(None),
+            }));
+        }
+
+        if self.type_match(vec![TokenType::This]) {
+            let keyword = self.previous();
+            return Ok(Expr::ThisExpr(ThisExpr {
+                span: keyword.span,
+                keyword,
+                local: Cell::new(None),
+            }));
+        }
+
         if self.type_match(vec![TokenType::Identifier]) {
+            let name = self.previous();
             return Ok(Expr::VariableExpr(VariableExpr {
-                name: self.previous(),
+                span: name.span,
+                name,
+                local: Cell::new(None),
             }));
         }
 
         if self.type_match(vec![TokenType::LeftParen]) {
+            let start = self.previous().span;
             let expression = self.expression()?;
             self.consume(
                 TokenType::RightParen,
                 ParseErrorMessage::ExpectRightParen(ExpectAfter::Expression),
-            )
-            .unwrap();
-            return Ok(Expr::GroupingExpr(Box::new(GroupingExpr { expression })));
+            )?;
+            return Ok(Expr::GroupingExpr(Box::new(GroupingExpr {
+                expression,
+                span: self.span_from(start),
+            })));
         }
 
         let error = ParseError {
             token: self.peek().clone(),
             message: ParseErrorMessage::ExpectExpression,
         };
-        return Err(report_parser_error(error));
+        return Err(error);
+    }
+
+    /// The span from `start` through the most recently consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        return start.to(self.previous().span);
+    }
+
+    /// Parses the rest of an interpolated string whose first segment has
+    /// just been consumed.
+    fn interpolation(&mut self) -> Result<Expr, ParseError> {
+        let start = self.previous().span;
+        let mut segments = vec![segment(&self.previous())];
+        let mut expressions = vec![];
+        loop {
+            expressions.push(self.expression()?);
+            if self.type_match(vec![TokenType::Interpolation]) {
+                segments.push(segment(&self.previous()));
+                continue;
+            }
+
+            let end = self.consume(
+                TokenType::InterpolationEnd,
+                ParseErrorMessage::ExpectBraceAfterInterpolation,
+            )?;
+            segments.push(segment(&end));
+            return Ok(Expr::InterpolationExpr(InterpolationExpr {
+                segments,
+                expressions,
+                span: start.to(end.span),
+            }));
+        }
     }
 
     fn type_match(&mut self, token_types: Vec<TokenType>) -> bool {
@@ -428,7 +748,7 @@ impl Parser {
             message,
             token: self.peek().clone(),
         };
-        return Err(report_parser_error(error));
+        return Err(error);
     }
 
     fn synchronize(&mut self) {
@@ -484,3 +804,13 @@ impl Parser {
         return self.peek().token_type == token_type;
     }
 }
+
+/// The text of a string segment token.
+fn segment(token: &Token) -> Rc<str> {
+    match &token.literal {
+        TokenLiteral::String(text) => text.clone(),
+        TokenLiteral::None | TokenLiteral::Number(_) => {
+            unreachable!("The lexer gives every string segment its text")
+        }
+    }
+}
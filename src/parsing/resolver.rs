@@ -0,0 +1,395 @@
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+
+use crate::lexing::token::Token;
+
+use super::{
+    error::{ParseError, ParseErrorMessage},
+    expr::{
+        AssignExpr, BinaryExpr, CallExpr, Expr, GetExpr, GroupingExpr, InterpolationExpr,
+        LocalSlot, LogicalExpr, SetExpr, SuperExpr, ThisExpr, UnaryExpr, VariableExpr,
+    },
+    stmt::{
+        BlockStmt, ClassStmt, ExpressionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt, Stmt,
+        VarStmt, WhileStmt,
+    },
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+/// A local variable in a scope being resolved.
+struct Variable {
+    /// Its position among the scope's variables, in order of declaration.
+    slot: usize,
+    /// Whether its initializer has finished.
+    defined: bool,
+}
+
+/// Static pass run between parsing and evaluation. It records on every
+/// variable-like expression how many scopes separate it from its binding and
+/// where in that scope it lives, and reports the errors that can be caught
+/// before the program runs.
+pub struct Resolver {
+    scopes: Vec<HashMap<Rc<str>, Variable>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    errors: Vec<ParseError>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![],
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            errors: vec![],
+        }
+    }
+
+    pub fn resolve(mut self, statements: &[Stmt]) -> Result<(), Vec<ParseError>> {
+        self.resolve_statements(statements);
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        return Err(self.errors);
+    }
+
+    fn resolve_statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            statement.resolve(self);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if scope.contains_key(&name.lexeme) {
+            self.error(name, ParseErrorMessage::AlreadyDeclared);
+            return;
+        }
+        let slot = scope.len();
+        scope.insert(name.lexeme.clone(), Variable { slot, defined: false });
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(variable) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.get_mut(&name.lexeme))
+        {
+            variable.defined = true;
+        }
+    }
+
+    /// Declares and defines a variable the interpreter binds implicitly, such
+    /// as `this`.
+    fn define_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let slot = scope.len();
+            scope.insert(Rc::from(name), Variable { slot, defined: true });
+        }
+    }
+
+    fn resolve_local(&mut self, name: &Token, local: &Cell<Option<LocalSlot>>) {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if let Some(variable) = scope.get(&name.lexeme) {
+                local.set(Some(LocalSlot {
+                    depth,
+                    slot: variable.slot,
+                }));
+                return;
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, function: &FunctionStmt, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_statements(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn error(&mut self, token: &Token, message: ParseErrorMessage) {
+        self.errors.push(ParseError {
+            token: token.clone(),
+            message,
+        });
+    }
+}
+
+trait Resolve {
+    fn resolve(&self, resolver: &mut Resolver);
+}
+
+impl Resolve for Stmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        match self {
+            Stmt::BlockStmt(stmt) => stmt.resolve(resolver),
+            Stmt::ExpressionStmt(stmt) => stmt.resolve(resolver),
+            Stmt::PrintStmt(stmt) => stmt.resolve(resolver),
+            Stmt::VarStmt(stmt) => stmt.resolve(resolver),
+            Stmt::IfStmt(stmt) => stmt.resolve(resolver),
+            Stmt::WhileStmt(stmt) => stmt.resolve(resolver),
+            Stmt::FunctionStmt(stmt) => stmt.resolve(resolver),
+            Stmt::ReturnStmt(stmt) => stmt.resolve(resolver),
+            Stmt::ClassStmt(stmt) => stmt.resolve(resolver),
+        }
+    }
+}
+
+impl Resolve for BlockStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        resolver.begin_scope();
+        resolver.resolve_statements(&self.statements);
+        resolver.end_scope();
+    }
+}
+
+impl Resolve for ExpressionStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.expression.resolve(resolver);
+    }
+}
+
+impl Resolve for PrintStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.expression.resolve(resolver);
+    }
+}
+
+impl Resolve for VarStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        resolver.declare(&self.name);
+        if let Some(initializer) = &self.initializer {
+            initializer.resolve(resolver);
+        }
+        resolver.define(&self.name);
+    }
+}
+
+impl Resolve for IfStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.condition.resolve(resolver);
+        self.then_branch.resolve(resolver);
+        if let Some(else_branch) = &self.else_branch {
+            else_branch.resolve(resolver);
+        }
+    }
+}
+
+impl Resolve for WhileStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.condition.resolve(resolver);
+        self.body.resolve(resolver);
+    }
+}
+
+impl Resolve for FunctionStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        resolver.declare(&self.name);
+        resolver.define(&self.name);
+        resolver.resolve_function(self, FunctionType::Function);
+    }
+}
+
+impl Resolve for ReturnStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        if resolver.current_function == FunctionType::None {
+            resolver.error(&self.keyword, ParseErrorMessage::ReturnFromTopLevel);
+        }
+
+        if let Some(value) = &self.value {
+            if resolver.current_function == FunctionType::Initializer {
+                resolver.error(&self.keyword, ParseErrorMessage::ReturnValueFromInitializer);
+            }
+            value.resolve(resolver);
+        }
+    }
+}
+
+impl Resolve for ClassStmt {
+    fn resolve(&self, resolver: &mut Resolver) {
+        let enclosing_class = resolver.current_class;
+        resolver.current_class = ClassType::Class;
+
+        resolver.declare(&self.name);
+        resolver.define(&self.name);
+
+        if let Some(superclass) = &self.superclass {
+            resolver.current_class = ClassType::Subclass;
+            superclass.resolve(resolver);
+            resolver.begin_scope();
+            resolver.define_name("super");
+        }
+
+        resolver.begin_scope();
+        resolver.define_name("this");
+        for method in &self.methods {
+            let function_type = if method.name.lexeme.as_ref() == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            resolver.resolve_function(method, function_type);
+        }
+        resolver.end_scope();
+
+        if self.superclass.is_some() {
+            resolver.end_scope();
+        }
+
+        resolver.current_class = enclosing_class;
+    }
+}
+
+impl Resolve for Expr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        match self {
+            Expr::AssignExpr(expr) => expr.resolve(resolver),
+            Expr::UnaryExpr(expr) => expr.resolve(resolver),
+            Expr::LiteralExpr(_) => {}
+            Expr::GroupingExpr(expr) => expr.resolve(resolver),
+            Expr::BinaryExpr(expr) => expr.resolve(resolver),
+            Expr::VariableExpr(expr) => expr.resolve(resolver),
+            Expr::LogicalExpr(expr) => expr.resolve(resolver),
+            Expr::CallExpr(expr) => expr.resolve(resolver),
+            Expr::GetExpr(expr) => expr.resolve(resolver),
+            Expr::SetExpr(expr) => expr.resolve(resolver),
+            Expr::ThisExpr(expr) => expr.resolve(resolver),
+            Expr::SuperExpr(expr) => expr.resolve(resolver),
+            Expr::InterpolationExpr(expr) => expr.resolve(resolver),
+        }
+    }
+}
+
+impl Resolve for AssignExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.value.resolve(resolver);
+        resolver.resolve_local(&self.name, &self.local);
+    }
+}
+
+impl Resolve for UnaryExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.right.resolve(resolver);
+    }
+}
+
+impl Resolve for GroupingExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.expression.resolve(resolver);
+    }
+}
+
+impl Resolve for BinaryExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.left.resolve(resolver);
+        self.right.resolve(resolver);
+    }
+}
+
+impl Resolve for VariableExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        let declared_but_undefined = resolver
+            .scopes
+            .last()
+            .and_then(|scope| scope.get(&self.name.lexeme))
+            .is_some_and(|variable| !variable.defined);
+        if declared_but_undefined {
+            resolver.error(&self.name, ParseErrorMessage::ReadLocalInOwnInitializer);
+        }
+
+        resolver.resolve_local(&self.name, &self.local);
+    }
+}
+
+impl Resolve for LogicalExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.left.resolve(resolver);
+        self.right.resolve(resolver);
+    }
+}
+
+impl Resolve for CallExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.callee.resolve(resolver);
+        for argument in &self.arguments {
+            argument.resolve(resolver);
+        }
+    }
+}
+
+impl Resolve for GetExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.object.resolve(resolver);
+    }
+}
+
+impl Resolve for SetExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        self.value.resolve(resolver);
+        self.object.resolve(resolver);
+    }
+}
+
+impl Resolve for ThisExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        if resolver.current_class == ClassType::None {
+            resolver.error(&self.keyword, ParseErrorMessage::ThisOutsideClass);
+            return;
+        }
+        resolver.resolve_local(&self.keyword, &self.local);
+    }
+}
+
+impl Resolve for SuperExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        match resolver.current_class {
+            ClassType::None => {
+                resolver.error(&self.keyword, ParseErrorMessage::SuperOutsideClass);
+            }
+            ClassType::Class => {
+                resolver.error(&self.keyword, ParseErrorMessage::SuperWithoutSuperclass);
+            }
+            ClassType::Subclass => {}
+        }
+        resolver.resolve_local(&self.keyword, &self.local);
+    }
+}
+
+impl Resolve for InterpolationExpr {
+    fn resolve(&self, resolver: &mut Resolver) {
+        for expression in &self.expressions {
+            expression.resolve(resolver);
+        }
+    }
+}
@@ -1,8 +1,11 @@
-use crate::lexing::token::Token;
+use std::rc::Rc;
 
-use super::expr::Expr;
+use crate::lexing::{span::Span, token::Token};
+
+use super::expr::{Expr, VariableExpr};
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Stmt {
     BlockStmt(BlockStmt),
     ExpressionStmt(ExpressionStmt),
@@ -10,27 +13,50 @@ pub enum Stmt {
     VarStmt(VarStmt),
     IfStmt(Box<IfStmt>),
     WhileStmt(Box<WhileStmt>),
+    FunctionStmt(Rc<FunctionStmt>),
+    ReturnStmt(ReturnStmt),
+    ClassStmt(ClassStmt),
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::BlockStmt(stmt) => stmt.span,
+            Stmt::ExpressionStmt(stmt) => stmt.span,
+            Stmt::PrintStmt(stmt) => stmt.span,
+            Stmt::VarStmt(stmt) => stmt.span,
+            Stmt::IfStmt(stmt) => stmt.span,
+            Stmt::WhileStmt(stmt) => stmt.span,
+            Stmt::FunctionStmt(stmt) => stmt.span,
+            Stmt::ReturnStmt(stmt) => stmt.span,
+            Stmt::ClassStmt(stmt) => stmt.span,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ExpressionStmt {
     pub expression: Expr,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct PrintStmt {
     pub expression: Expr,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct VarStmt {
     pub name: Token,
     pub initializer: Option<Expr>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct BlockStmt {
     pub statements: Vec<Stmt>,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
@@ -38,10 +64,35 @@ pub struct IfStmt {
     pub condition: Expr,
     pub then_branch: Stmt,
     pub else_branch: Option<Stmt>,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
 pub struct WhileStmt {
     pub condition: Expr,
     pub body: Stmt,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionStmt {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReturnStmt {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClassStmt {
+    pub name: Token,
+    pub superclass: Option<VariableExpr>,
+    pub methods: Vec<Rc<FunctionStmt>>,
+    pub span: Span,
 }
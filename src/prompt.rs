@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::interpreter::run;
+use crate::{cli::Options, interpreter::run};
 
 fn prompt(name: &str) -> String {
     let mut line = String::new();
@@ -13,12 +13,13 @@ fn prompt(name: &str) -> String {
     return line.trim().to_string();
 }
 
-pub fn run_prompt() {
+pub fn run_prompt(options: &Options) {
     loop {
         let input = prompt("> ");
         if input == "exit" {
             break;
         }
-        run(input).unwrap();
+        // Errors have already been reported; keep the session going.
+        let _ = run(input, options);
     }
 }
@@ -0,0 +1,76 @@
+use std::cell::Cell;
+
+use crate::{lexing::token::Token, parsing::expr::LiteralValue};
+
+use super::error::{RuntimeError, RuntimeErrorMessage};
+
+/// How many calls may be active at once, in either backend, before the
+/// program is stopped with a stack overflow.
+pub const MAX_CALL_DEPTH: usize = 10_000;
+
+/// The size of the native stack the interpreter runs on. The tree-walking
+/// backend recurses on it for every Lox call.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// How much of the native stack active calls may use. Calls whose
+/// expressions nest deeply need more of it each, so they can run out of
+/// stack before reaching `MAX_CALL_DEPTH`; the rest is left for the frames
+/// between two calls.
+const MAX_STACK_USE: usize = STACK_SIZE / 8 * 7;
+
+#[derive(Clone, Copy)]
+struct CallStack {
+    depth: usize,
+    /// Where the native stack was when the outermost active call started.
+    base: usize,
+}
+
+thread_local! {
+    static CALL_STACK: Cell<CallStack> = const { Cell::new(CallStack { depth: 0, base: 0 }) };
+}
+
+pub trait LoxCallable {
+    fn arity(&self) -> usize;
+    fn call(&self, arguments: Vec<LiteralValue>) -> Result<LiteralValue, RuntimeError>;
+}
+
+/// Counts one active call for as long as it is alive.
+pub struct CallDepth;
+
+impl CallDepth {
+    /// Enters a call made at `paren`, or reports a stack overflow if too many
+    /// are active already.
+    pub fn enter(paren: &Token) -> Result<CallDepth, RuntimeError> {
+        let position = stack_position();
+        return CALL_STACK.with(|stack| {
+            let mut current = stack.get();
+            if current.depth == 0 {
+                current.base = position;
+            }
+            let stack_use = current.base.abs_diff(position);
+            if current.depth >= MAX_CALL_DEPTH || stack_use > MAX_STACK_USE {
+                return Err(RuntimeError::new(paren, RuntimeErrorMessage::StackOverflow));
+            }
+            current.depth += 1;
+            stack.set(current);
+            return Ok(CallDepth);
+        });
+    }
+}
+
+impl Drop for CallDepth {
+    fn drop(&mut self) {
+        CALL_STACK.with(|stack| {
+            let mut current = stack.get();
+            current.depth -= 1;
+            stack.set(current);
+        });
+    }
+}
+
+/// The address of a local variable, which tells how deep the native stack
+/// currently is.
+fn stack_position() -> usize {
+    let marker = 0u8;
+    return std::hint::black_box(&marker) as *const u8 as usize;
+}
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    gc::{self, Address, Trace},
+    parsing::expr::LiteralValue,
+};
+
+use super::{
+    callable::LoxCallable, error::RuntimeError, function::LoxFunction, instance::LoxInstance,
+};
+
+pub struct LoxClass {
+    pub name: Rc<str>,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<Rc<str>, Rc<LoxFunction>>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: Rc<str>,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<Rc<str>, Rc<LoxFunction>>,
+    ) -> Self {
+        Self {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    /// Finds `name` on this class or the nearest superclass that defines it.
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(method.clone());
+        }
+        if let Some(superclass) = &self.superclass {
+            return superclass.find_method(name);
+        }
+        return None;
+    }
+}
+
+impl LoxCallable for Rc<LoxClass> {
+    fn arity(&self) -> usize {
+        if let Some(initializer) = self.find_method("init") {
+            return initializer.arity();
+        }
+        return 0;
+    }
+
+    fn call(&self, arguments: Vec<LiteralValue>) -> Result<LiteralValue, RuntimeError> {
+        let instance = gc::track(Rc::new(LoxInstance::new(self.clone())));
+        if let Some(initializer) = self.find_method("init") {
+            initializer.bind(instance.clone()).call(arguments)?;
+        }
+        return Ok(LiteralValue::Instance(instance));
+    }
+}
+
+impl Trace for LoxClass {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        if let Some(superclass) = &self.superclass {
+            visit(gc::address(superclass));
+        }
+        for method in self.methods.values() {
+            visit(gc::address(method));
+        }
+    }
+
+    /// A class can't be changed once it is declared, so the cycles it is part
+    /// of are broken through the environments its methods close over.
+    fn clear(&self) {}
+}
+
+impl std::fmt::Debug for LoxClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
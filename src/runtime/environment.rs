@@ -1,53 +1,159 @@
 use std::{collections::HashMap, cell::RefCell, rc::Rc};
-use crate::{lexing::token::Token, parsing::expr::LiteralValue};
+use crate::{
+    gc::{self, Address, Trace},
+    lexing::token::Token,
+    parsing::expr::{LiteralValue, LocalSlot},
+};
 use super::error::{RuntimeError, RuntimeErrorMessage};
 
+/// One scope of variables. Locals are stored by the slot the resolver gave
+/// them; only the outermost scope, which holds the globals, is keyed by name.
 #[derive(Clone)]
 pub struct Environment {
     enclosing: Option<Rc<RefCell<Environment>>>,
-    values: HashMap<String, LiteralValue>,
+    slots: Vec<LiteralValue>,
+    globals: HashMap<Rc<str>, LiteralValue>,
+    /// Whether the collector tracks this environment, which it only does
+    /// once a closure has captured it.
+    tracked: bool,
 }
 
 impl Environment {
     pub fn new(enclosing: Option<Rc<RefCell<Environment>>>) -> Self {
         Self {
             enclosing,
-            values: HashMap::new(),
+            slots: vec![],
+            globals: HashMap::new(),
+            tracked: false,
         }
     }
 
-    pub fn assign(&mut self, token: Token, value: LiteralValue) -> Result<(), RuntimeError> {
-        if let Some(_) = self.values.get(&token.lexeme) {
-            self.values.entry(token.lexeme).and_modify(|v| {
-                *v = value;
-            });
-            return Ok(());
-        };
-        if let Some(enclosing) = &mut self.enclosing {
-            return enclosing.borrow_mut().assign(token, value);
+    pub fn share(self) -> Rc<RefCell<Environment>> {
+        return Rc::new(RefCell::new(self));
+    }
+
+    /// Starts tracking `environment` and the scopes enclosing it, because a
+    /// closure is capturing them. Scopes no closure captures can't be part of
+    /// a cycle, so reference counting alone frees them.
+    pub fn capture(environment: &Rc<RefCell<Environment>>) {
+        let mut current = environment.clone();
+        loop {
+            let enclosing = {
+                let mut scope = current.borrow_mut();
+                if scope.tracked {
+                    return;
+                }
+                scope.tracked = true;
+                scope.enclosing.clone()
+            };
+            gc::track(current);
+            let Some(enclosing) = enclosing else {
+                return;
+            };
+            current = enclosing;
+        }
+    }
+
+    /// Binds the next variable of this scope. Locals must be defined in the
+    /// order the resolver declared them so that they land in their slots.
+    pub fn define(&mut self, name: &Rc<str>, value: LiteralValue) {
+        if self.enclosing.is_some() {
+            self.slots.push(value);
+            return;
+        }
+        self.globals.insert(name.clone(), value);
+    }
+
+    /// Binds the next slot of a local scope to a variable the interpreter
+    /// introduces itself, such as `this` or `super`.
+    pub fn define_local(&mut self, value: LiteralValue) {
+        self.slots.push(value);
+    }
+
+    /// Reads the local at `local`, or `None` if it has not been defined yet.
+    pub fn get_at(&self, local: LocalSlot) -> Option<LiteralValue> {
+        if local.depth == 0 {
+            return self.slots.get(local.slot).cloned();
+        }
+        let outer = LocalSlot {
+            depth: local.depth - 1,
+            slot: local.slot,
         };
-        return Err(RuntimeError {
-            token: token.clone(),
-            message: RuntimeErrorMessage::UndefinedVariable(token.lexeme),
-        });
+        return self.enclosing.as_ref()?.borrow().get_at(outer);
     }
 
-    pub fn define(&mut self, name: String, value: LiteralValue) {
-        self.values.insert(name, value);
+    pub fn assign_at(
+        &mut self,
+        local: LocalSlot,
+        token: &Token,
+        value: LiteralValue,
+    ) -> Result<(), RuntimeError> {
+        if local.depth > 0 {
+            if let Some(enclosing) = &self.enclosing {
+                let outer = LocalSlot {
+                    depth: local.depth - 1,
+                    slot: local.slot,
+                };
+                return enclosing.borrow_mut().assign_at(outer, token, value);
+            }
+        } else if let Some(slot) = self.slots.get_mut(local.slot) {
+            *slot = value;
+            return Ok(());
+        }
+
+        return Err(undefined_variable(token));
     }
 
-    pub fn get(&self, token: Token) -> Result<LiteralValue, RuntimeError> {
-        if let Some(value) = self.values.get(&token.lexeme) {
-            return Ok(value.clone());
+    /// Reads `token` from the outermost (global) scope.
+    pub fn get_global(&self, token: &Token) -> Result<LiteralValue, RuntimeError> {
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow().get_global(token);
         }
+        return self
+            .globals
+            .get(&token.lexeme)
+            .cloned()
+            .ok_or_else(|| undefined_variable(token));
+    }
 
+    pub fn assign_global(&mut self, token: &Token, value: LiteralValue) -> Result<(), RuntimeError> {
         if let Some(enclosing) = &self.enclosing {
-            return enclosing.borrow().get(token);
+            return enclosing.borrow_mut().assign_global(token, value);
+        }
+        let Some(slot) = self.globals.get_mut(&token.lexeme) else {
+            return Err(undefined_variable(token));
+        };
+        *slot = value;
+        return Ok(());
+    }
+}
+
+impl Trace for RefCell<Environment> {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        // An environment that is being written to belongs to running code,
+        // which keeps everything it refers to alive anyway.
+        let Ok(environment) = self.try_borrow() else {
+            return;
+        };
+        if let Some(enclosing) = &environment.enclosing {
+            visit(gc::address(enclosing));
         }
+        for value in environment.slots.iter().chain(environment.globals.values()) {
+            value.trace(visit);
+        }
+    }
 
-        return Err(RuntimeError {
-            token: token.clone(),
-            message: RuntimeErrorMessage::UndefinedVariable(token.lexeme),
-        });
+    fn clear(&self) {
+        let Ok(mut environment) = self.try_borrow_mut() else {
+            return;
+        };
+        environment.enclosing = None;
+        environment.slots.clear();
+        environment.globals.clear();
     }
 }
+
+pub fn undefined_variable(token: &Token) -> RuntimeError {
+    let message = RuntimeErrorMessage::UndefinedVariable(token.lexeme.to_string());
+    return RuntimeError::new(token, message);
+}
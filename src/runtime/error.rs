@@ -1,4 +1,8 @@
-use crate::{error::report_error, lexing::token::Token};
+use crate::{
+    error::{Diagnostic, Label},
+    lexing::{span::Span, token::Token},
+    parsing::expr::LiteralValue,
+};
 
 #[derive(Debug, Clone)]
 pub enum RuntimeErrorMessage {
@@ -6,6 +10,15 @@ pub enum RuntimeErrorMessage {
     OperandsMustBeNumbers,
     OperandsMustBeNumberOrString,
     UndefinedVariable(String),
+    NotCallable,
+    WrongArity { expected: usize, got: usize },
+    UndefinedProperty(String),
+    OnlyInstancesHaveProperties,
+    OnlyInstancesHaveFields,
+    SuperclassMustBeClass,
+    InheritFromSelf,
+    SuperOutsideSubclass,
+    StackOverflow,
 }
 
 impl std::fmt::Display for RuntimeErrorMessage {
@@ -14,22 +27,107 @@ impl std::fmt::Display for RuntimeErrorMessage {
             RuntimeErrorMessage::OperandMustBeNumber => write!(f, "Operand must be a number."),
             RuntimeErrorMessage::OperandsMustBeNumbers => write!(f, "Operands must be numbers."),
             RuntimeErrorMessage::OperandsMustBeNumberOrString => {
-                write!(f, "Operands must be two numbers or two strings")
+                write!(f, "Operands must be two numbers or include a string")
             }
             RuntimeErrorMessage::UndefinedVariable(name) => {
                 write!(f, "Undefined variable '{}'.", name)
             }
+            RuntimeErrorMessage::NotCallable => write!(f, "Can only call functions and classes."),
+            RuntimeErrorMessage::WrongArity { expected, got } => {
+                write!(f, "Expected {} arguments but got {}.", expected, got)
+            }
+            RuntimeErrorMessage::UndefinedProperty(name) => {
+                write!(f, "Undefined property '{}'.", name)
+            }
+            RuntimeErrorMessage::OnlyInstancesHaveProperties => {
+                write!(f, "Only instances have properties.")
+            }
+            RuntimeErrorMessage::OnlyInstancesHaveFields => {
+                write!(f, "Only instances have fields.")
+            }
+            RuntimeErrorMessage::SuperclassMustBeClass => write!(f, "Superclass must be a class."),
+            RuntimeErrorMessage::InheritFromSelf => {
+                write!(f, "A class can't inherit from itself.")
+            }
+            RuntimeErrorMessage::SuperOutsideSubclass => {
+                write!(f, "Can't use 'super' outside of a subclass.")
+            }
+            RuntimeErrorMessage::StackOverflow => write!(f, "Stack overflow."),
+        }
+    }
+}
+
+impl RuntimeErrorMessage {
+    /// The stable identifier tools can match this error on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeErrorMessage::OperandMustBeNumber => "E0201",
+            RuntimeErrorMessage::OperandsMustBeNumbers => "E0202",
+            RuntimeErrorMessage::OperandsMustBeNumberOrString => "E0203",
+            RuntimeErrorMessage::UndefinedVariable(_) => "E0204",
+            RuntimeErrorMessage::NotCallable => "E0205",
+            RuntimeErrorMessage::WrongArity { .. } => "E0206",
+            RuntimeErrorMessage::UndefinedProperty(_) => "E0207",
+            RuntimeErrorMessage::OnlyInstancesHaveProperties => "E0208",
+            RuntimeErrorMessage::OnlyInstancesHaveFields => "E0209",
+            RuntimeErrorMessage::SuperclassMustBeClass => "E0210",
+            RuntimeErrorMessage::InheritFromSelf => "E0211",
+            RuntimeErrorMessage::SuperOutsideSubclass => "E0212",
+            RuntimeErrorMessage::StackOverflow => "E0213",
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct RuntimeError {
-    pub token: Token,
+    pub span: Span,
     pub message: RuntimeErrorMessage,
+    pub labels: Vec<Label>,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, message: RuntimeErrorMessage) -> Self {
+        return Self::at(token.span, message);
+    }
+
+    pub fn at(span: Span, message: RuntimeErrorMessage) -> Self {
+        Self {
+            span,
+            message,
+            labels: vec![],
+        }
+    }
+
+    /// Points at the other spans, such as operands, that explain the error.
+    pub fn with_labels(mut self, labels: Vec<Label>) -> Self {
+        self.labels = labels;
+        return self;
+    }
+}
+
+/// Labels an operand of a failed operation with the type of its value, such
+/// as "a string".
+pub fn operand_label(span: Span, type_name: &str) -> Label {
+    return Label::new(span, Some(format!("this is {}", type_name)));
 }
 
-pub fn report_runtime_error(error: RuntimeError) -> RuntimeError {
-    report_error(error.token.line, "", &error.message.to_string());
-    return error;
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        return Diagnostic::error(error.message.code(), error.message.to_string(), error.span)
+            .with_labels(error.labels.clone());
+    }
+}
+
+/// Anything that stops a statement from running to completion: either a runtime
+/// error or a `return` unwinding to the enclosing function call.
+#[derive(Debug, Clone)]
+pub enum Interrupt {
+    Error(RuntimeError),
+    Return(LiteralValue),
+}
+
+impl From<RuntimeError> for Interrupt {
+    fn from(error: RuntimeError) -> Self {
+        Interrupt::Error(error)
+    }
 }
@@ -1,22 +1,30 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::parsing::{
-    expr::LiteralValue,
-    stmt::{BlockStmt, ExpressionStmt, IfStmt, PrintStmt, Stmt, VarStmt, WhileStmt},
+use crate::{
+    gc,
+    parsing::{
+        expr::LiteralValue,
+        stmt::{
+            BlockStmt, ClassStmt, ExpressionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt,
+            Stmt, VarStmt, WhileStmt,
+        },
+    },
 };
 
 use super::{
+    class::LoxClass,
     environment::Environment,
-    error::RuntimeError,
+    error::{Interrupt, RuntimeError, RuntimeErrorMessage},
+    function::LoxFunction,
     interpret::{is_truthy, ExprInterpret},
 };
 
 pub trait EvaluateStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError>;
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt>;
 }
 
 impl EvaluateStmt for Stmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
         match self {
             Stmt::ExpressionStmt(stmt) => stmt.evaluate(environment),
             Stmt::PrintStmt(stmt) => stmt.evaluate(environment),
@@ -24,56 +32,56 @@ impl EvaluateStmt for Stmt {
             Stmt::BlockStmt(stmt) => stmt.evaluate(environment),
             Stmt::IfStmt(stmt) => stmt.evaluate(environment),
             Stmt::WhileStmt(stmt) => stmt.evaluate(environment),
+            Stmt::FunctionStmt(stmt) => stmt.evaluate(environment),
+            Stmt::ReturnStmt(stmt) => stmt.evaluate(environment),
+            Stmt::ClassStmt(stmt) => stmt.evaluate(environment),
         }
     }
 }
 
 impl EvaluateStmt for ExpressionStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
         self.expression.interpret(environment)?;
         Ok(())
     }
 }
 
 impl EvaluateStmt for PrintStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
         let value = self.expression.interpret(environment)?;
-        println!("{:?}", value);
+        println!("{}", value);
         Ok(())
     }
 }
 
 impl EvaluateStmt for VarStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
         let mut value = LiteralValue::None;
         if let Some(expr) = &self.initializer {
-            value = expr.interpret(environment.clone())?;
+            value = expr.interpret(environment)?;
         }
 
-        environment.borrow_mut().define(self.name.lexeme.clone(), value);
+        environment.borrow_mut().define(&self.name.lexeme, value);
 
         Ok(())
     }
 }
 
 impl EvaluateStmt for BlockStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
-        let current_environment = Rc::new(RefCell::new(Environment::new(Some(environment))));
-        for statement in &mut self.statements {
-            statement.evaluate(current_environment.clone())?;
-        }
-        return Ok(());
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
+        let current_environment = Environment::new(Some(environment.clone()));
+        return execute_block(&self.statements, current_environment.share());
     }
 }
 
 impl EvaluateStmt for IfStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
-        let result = self.condition.interpret(environment.clone())?;
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
+        let result = self.condition.interpret(environment)?;
         if is_truthy(&result) {
             self.then_branch.evaluate(environment)?;
             return Ok(());
         }
-        if let Some(else_branch) = &mut self.else_branch {
+        if let Some(else_branch) = &self.else_branch {
             else_branch.evaluate(environment)?;
         }
         return Ok(());
@@ -81,10 +89,84 @@ impl EvaluateStmt for IfStmt {
 }
 
 impl EvaluateStmt for WhileStmt {
-    fn evaluate(&mut self, environment: Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
-        while is_truthy(&self.condition.interpret(environment.clone())?) {
-            self.body.evaluate(environment.clone())?;
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
+        while is_truthy(&self.condition.interpret(environment)?) {
+            self.body.evaluate(environment)?;
+        }
+        return Ok(());
+    }
+}
+
+impl EvaluateStmt for ReturnStmt {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
+        let mut value = LiteralValue::None;
+        if let Some(expr) = &self.value {
+            value = expr.interpret(environment)?;
         }
+        return Err(Interrupt::Return(value));
+    }
+}
+
+impl EvaluateStmt for Rc<FunctionStmt> {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
+        let function = LoxFunction::new(self.clone(), environment.clone(), false);
+        let function = gc::track(Rc::new(function));
+        environment
+            .borrow_mut()
+            .define(&self.name.lexeme, LiteralValue::Function(function));
         return Ok(());
     }
 }
+
+impl EvaluateStmt for ClassStmt {
+    fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<(), Interrupt> {
+        let mut superclass = None;
+        let mut method_environment = environment.clone();
+        if let Some(superclass_expr) = &self.superclass {
+            if superclass_expr.name.lexeme == self.name.lexeme {
+                return Err(Interrupt::Error(RuntimeError::new(
+                    &superclass_expr.name,
+                    RuntimeErrorMessage::InheritFromSelf,
+                )));
+            }
+            let LiteralValue::Class(class) = superclass_expr.interpret(environment)? else {
+                return Err(Interrupt::Error(RuntimeError::new(
+                    &superclass_expr.name,
+                    RuntimeErrorMessage::SuperclassMustBeClass,
+                )));
+            };
+
+            let mut super_environment = Environment::new(Some(environment.clone()));
+            super_environment.define_local(LiteralValue::Class(class.clone()));
+            method_environment = super_environment.share();
+            superclass = Some(class);
+        }
+
+        let mut methods = HashMap::new();
+        for method in &self.methods {
+            let is_initializer = method.name.lexeme.as_ref() == "init";
+            let function =
+                LoxFunction::new(method.clone(), method_environment.clone(), is_initializer);
+            methods.insert(method.name.lexeme.clone(), gc::track(Rc::new(function)));
+        }
+
+        let class = LoxClass::new(self.name.lexeme.clone(), superclass, methods);
+        let class = gc::track(Rc::new(class));
+        environment
+            .borrow_mut()
+            .define(&self.name.lexeme, LiteralValue::Class(class));
+        return Ok(());
+    }
+}
+
+/// Runs `statements` inside `environment`, which the caller has already set up
+/// as a fresh scope.
+pub fn execute_block(
+    statements: &[Stmt],
+    environment: Rc<RefCell<Environment>>,
+) -> Result<(), Interrupt> {
+    for statement in statements {
+        statement.evaluate(&environment)?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,93 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    gc::{self, Address, Trace},
+    parsing::{
+        expr::{LiteralValue, LocalSlot},
+        stmt::FunctionStmt,
+    },
+};
+
+use super::{
+    callable::LoxCallable,
+    environment::Environment,
+    error::{Interrupt, RuntimeError},
+    evaluate::execute_block,
+    instance::LoxInstance,
+};
+
+pub struct LoxFunction {
+    declaration: Rc<FunctionStmt>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(
+        declaration: Rc<FunctionStmt>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        Environment::capture(&closure);
+        Self {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Returns a copy of this method whose closure has `this` bound to `instance`.
+    pub fn bind(&self, instance: Rc<LoxInstance>) -> LoxFunction {
+        let mut environment = Environment::new(Some(self.closure.clone()));
+        environment.define_local(LiteralValue::Instance(instance));
+        return LoxFunction::new(
+            self.declaration.clone(),
+            environment.share(),
+            self.is_initializer,
+        );
+    }
+
+    fn bound_this(&self) -> LiteralValue {
+        return self
+            .closure
+            .borrow()
+            .get_at(LocalSlot { depth: 0, slot: 0 })
+            .unwrap_or(LiteralValue::None);
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> usize {
+        return self.declaration.params.len();
+    }
+
+    fn call(&self, arguments: Vec<LiteralValue>) -> Result<LiteralValue, RuntimeError> {
+        let mut call_environment = Environment::new(Some(self.closure.clone()));
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            call_environment.define(&param.lexeme, argument);
+        }
+
+        match execute_block(&self.declaration.body, call_environment.share()) {
+            Ok(()) | Err(Interrupt::Return(_)) if self.is_initializer => Ok(self.bound_this()),
+            Ok(()) => Ok(LiteralValue::None),
+            Err(Interrupt::Return(value)) => Ok(value),
+            Err(Interrupt::Error(error)) => Err(error),
+        }
+    }
+}
+
+impl Trace for LoxFunction {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        visit(gc::address(&self.closure));
+    }
+
+    /// The closure is fixed when the function is created, so the cycles it
+    /// is part of are broken by clearing the environment instead.
+    fn clear(&self) {}
+}
+
+impl std::fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<fn {}>", self.declaration.name.lexeme)
+    }
+}
@@ -0,0 +1,70 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    gc::{self, Address, Trace},
+    lexing::token::Token,
+    parsing::expr::LiteralValue,
+};
+
+use super::{
+    class::LoxClass,
+    error::{RuntimeError, RuntimeErrorMessage},
+};
+
+pub struct LoxInstance {
+    class: Rc<LoxClass>,
+    fields: RefCell<HashMap<Rc<str>, LiteralValue>>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        Self {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Looks `name` up as a field first, then as a method bound to `instance`.
+    pub fn get(instance: &Rc<LoxInstance>, name: &Token) -> Result<LiteralValue, RuntimeError> {
+        if let Some(value) = instance.fields.borrow().get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        if let Some(method) = instance.class.find_method(&name.lexeme) {
+            let bound = method.bind(instance.clone());
+            return Ok(LiteralValue::Function(gc::track(Rc::new(bound))));
+        }
+
+        return Err(RuntimeError::new(
+            name,
+            RuntimeErrorMessage::UndefinedProperty(name.lexeme.to_string()),
+        ));
+    }
+
+    pub fn set(&self, name: &Token, value: LiteralValue) {
+        self.fields.borrow_mut().insert(name.lexeme.clone(), value);
+    }
+}
+
+impl Trace for LoxInstance {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        visit(gc::address(&self.class));
+        if let Ok(fields) = self.fields.try_borrow() {
+            for value in fields.values() {
+                value.trace(visit);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut fields) = self.fields.try_borrow_mut() {
+            fields.clear();
+        }
+    }
+}
+
+impl std::fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
@@ -1,22 +1,30 @@
 use std::{rc::Rc, cell::RefCell};
 
 use crate::{
-    lexing::token::TokenType,
+    gc,
+    lexing::token::{Token, TokenType},
     parsing::expr::{
-        AssignExpr, BinaryExpr, Expr, GroupingExpr, LiteralExpr, LiteralValue, LogicalExpr,
+        AssignExpr, BinaryExpr, CallExpr, Expr, GetExpr, GroupingExpr, InterpolationExpr,
+        LiteralExpr, LiteralValue, LocalSlot, LogicalExpr, SetExpr, SuperExpr, ThisExpr,
         UnaryExpr, VariableExpr,
     },
-    runtime::error::{report_runtime_error, RuntimeErrorMessage},
+    error::Label,
+    runtime::error::{self, RuntimeErrorMessage},
 };
 
-use super::{environment::Environment, error::RuntimeError};
+use super::{
+    callable::{CallDepth, LoxCallable},
+    environment::{undefined_variable, Environment},
+    error::RuntimeError,
+    instance::LoxInstance,
+};
 
 pub trait ExprInterpret {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError>;
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError>;
 }
 
 impl ExprInterpret for Expr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
         match self {
             Expr::UnaryExpr(expr) => expr.interpret(environment),
             Expr::LiteralExpr(expr) => expr.interpret(environment),
@@ -24,37 +32,51 @@ impl ExprInterpret for Expr {
             Expr::BinaryExpr(expr) => expr.interpret(environment),
             Expr::VariableExpr(expr) => expr.interpret(environment),
             Expr::AssignExpr(expr) => expr.interpret(environment),
-            Expr::LogicalExpr(_) => todo!(),
+            Expr::LogicalExpr(expr) => expr.interpret(environment),
+            Expr::CallExpr(expr) => expr.interpret(environment),
+            Expr::GetExpr(expr) => expr.interpret(environment),
+            Expr::SetExpr(expr) => expr.interpret(environment),
+            Expr::ThisExpr(expr) => expr.interpret(environment),
+            Expr::SuperExpr(expr) => expr.interpret(environment),
+            Expr::InterpolationExpr(expr) => expr.interpret(environment),
         }
     }
 }
 
 impl ExprInterpret for AssignExpr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
-        let value = self.value.interpret(environment.clone())?;
-        environment.borrow_mut().assign(self.name.clone(), value.clone())?;
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let value = self.value.interpret(environment)?;
+        let result = match self.local.get() {
+            Some(local) => environment
+                .borrow_mut()
+                .assign_at(local, &self.name, value.clone()),
+            None => environment.borrow_mut().assign_global(&self.name, value.clone()),
+        };
+        result?;
         return Ok(value);
     }
 }
 
 impl ExprInterpret for LiteralExpr {
-    fn interpret(&self, _environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+    fn interpret(&self, _environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
         return Ok(self.value.clone());
     }
 }
 
 impl ExprInterpret for UnaryExpr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
         let right = self.right.interpret(environment)?;
         match self.operator.token_type {
             TokenType::Minus => {
                 if let LiteralValue::Number(number) = right {
                     return Ok(LiteralValue::Number(-number));
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandMustBeNumber,
-                }));
+                let label = operand_label(&self.right, &right);
+                return Err(RuntimeError::new(
+                    &self.operator,
+                    RuntimeErrorMessage::OperandMustBeNumber,
+                )
+                .with_labels(vec![label]));
             }
             TokenType::Bang => {
                 return Ok(LiteralValue::Bool(!is_truthy(&right)));
@@ -67,111 +89,94 @@ impl ExprInterpret for UnaryExpr {
 }
 
 impl ExprInterpret for GroupingExpr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
         self.expression.interpret(environment)
     }
 }
 
 impl ExprInterpret for BinaryExpr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
-        let left = self.left.interpret(environment.clone())?;
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let left = self.left.interpret(environment)?;
         let right = self.right.interpret(environment)?;
         match self.operator.token_type {
             TokenType::Plus => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Number(left + right));
                     }
                 }
-                if let LiteralValue::String(left) = left {
-                    if let LiteralValue::String(right) = right {
-                        let mut result = String::from(&left);
-                        result.push_str(&right);
-                        return Ok(LiteralValue::String(result));
+                if let LiteralValue::String(left) = &left {
+                    if let LiteralValue::String(right) = &right {
+                        let mut result = String::with_capacity(left.len() + right.len());
+                        result.push_str(left);
+                        result.push_str(right);
+                        return Ok(LiteralValue::String(Rc::from(result)));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumberOrString,
-                }));
+                // A string turns the other operand into its text.
+                if matches!(left, LiteralValue::String(_))
+                    || matches!(right, LiteralValue::String(_))
+                {
+                    return Ok(LiteralValue::String(Rc::from(format!("{}{}", left, right))));
+                }
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumberOrString));
             }
             TokenType::Minus => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Number(left - right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
             TokenType::Slash => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Number(left / right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
             TokenType::Star => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Number(left * right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
 
             TokenType::Less => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Bool(left < right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
             TokenType::LessEqual => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Bool(left <= right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
             TokenType::Greater => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Bool(left > right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
             TokenType::GreaterEqual => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
+                if let LiteralValue::Number(left) = &left {
+                    if let LiteralValue::Number(right) = &right {
                         return Ok(LiteralValue::Bool(left >= right));
                     }
                 }
-                return Err(report_runtime_error(RuntimeError {
-                    token: self.operator.clone(),
-                    message: RuntimeErrorMessage::OperandsMustBeNumbers,
-                }));
+                return Err(self.operands_error(&left, &right, RuntimeErrorMessage::OperandsMustBeNumbers));
             }
 
             TokenType::EqualEqual => {
@@ -187,36 +192,178 @@ impl ExprInterpret for BinaryExpr {
     }
 }
 
+impl BinaryExpr {
+    fn operands_error(
+        &self,
+        left: &LiteralValue,
+        right: &LiteralValue,
+        message: RuntimeErrorMessage,
+    ) -> RuntimeError {
+        let labels = vec![
+            operand_label(&self.left, left),
+            operand_label(&self.right, right),
+        ];
+        return RuntimeError::new(&self.operator, message).with_labels(labels);
+    }
+}
+
 impl ExprInterpret for VariableExpr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
-        environment.borrow().get(self.name.clone())
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        look_up_variable(environment, &self.name, self.local.get())
     }
 }
 
 impl ExprInterpret for LogicalExpr {
-    fn interpret(&self, environment: Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
-        let left = self.left.interpret(environment.clone())?;
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let left = self.left.interpret(environment)?;
 
-        if self.operator.token_type == TokenType::Or {
-            if is_truthy(&left) {
-                return Ok(left);
-            }
-        } 
-        if self.operator.token_type == TokenType::And {
-            if !is_truthy(&left) {
-                return Ok(left);
-            }
+        if self.operator.token_type == TokenType::Or && is_truthy(&left) {
+            return Ok(left);
+        }
+        if self.operator.token_type == TokenType::And && !is_truthy(&left) {
+            return Ok(left);
         }
 
         return self.right.interpret(environment);
     }
 }
 
+impl ExprInterpret for CallExpr {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let callee = self.callee.interpret(environment)?;
+
+        let mut arguments = vec![];
+        for argument in &self.arguments {
+            arguments.push(argument.interpret(environment)?);
+        }
+
+        let callable: &dyn LoxCallable = match &callee {
+            LiteralValue::Function(function) => function.as_ref(),
+            LiteralValue::Native(native) => native.as_ref(),
+            LiteralValue::Class(class) => class,
+            _ => {
+                return Err(RuntimeError::new(&self.paren, RuntimeErrorMessage::NotCallable));
+            }
+        };
+
+        if arguments.len() != callable.arity() {
+            return Err(RuntimeError::new(
+                &self.paren,
+                RuntimeErrorMessage::WrongArity {
+                    expected: callable.arity(),
+                    got: arguments.len(),
+                },
+            ));
+        }
+
+        // Natives run without a frame of their own, as in the virtual machine.
+        let _depth = match callee {
+            LiteralValue::Native(_) => None,
+            _ => Some(CallDepth::enter(&self.paren)?),
+        };
+        return callable.call(arguments);
+    }
+}
+
+impl ExprInterpret for GetExpr {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let object = self.object.interpret(environment)?;
+        if let LiteralValue::Instance(instance) = object {
+            return LoxInstance::get(&instance, &self.name);
+        }
+
+        return Err(RuntimeError::new(&self.name, RuntimeErrorMessage::OnlyInstancesHaveProperties));
+    }
+}
+
+impl ExprInterpret for SetExpr {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let object = self.object.interpret(environment)?;
+        let LiteralValue::Instance(instance) = object else {
+            return Err(RuntimeError::new(&self.name, RuntimeErrorMessage::OnlyInstancesHaveFields));
+        };
+
+        let value = self.value.interpret(environment)?;
+        instance.set(&self.name, value.clone());
+        return Ok(value);
+    }
+}
+
+impl ExprInterpret for ThisExpr {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        look_up_variable(environment, &self.keyword, self.local.get())
+    }
+}
+
+impl ExprInterpret for SuperExpr {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let environment = environment.borrow();
+        // `this` is the only variable of the scope just inside the one
+        // holding `super`.
+        let bindings = self.local.get().map(|local| {
+            let this = LocalSlot {
+                depth: local.depth - 1,
+                slot: 0,
+            };
+            (environment.get_at(local), environment.get_at(this))
+        });
+        let Some((Some(LiteralValue::Class(superclass)), Some(LiteralValue::Instance(instance)))) =
+            bindings
+        else {
+            return Err(RuntimeError::new(&self.keyword, RuntimeErrorMessage::SuperOutsideSubclass));
+        };
+
+        let Some(method) = superclass.find_method(&self.method.lexeme) else {
+            return Err(RuntimeError::new(
+                &self.method,
+                RuntimeErrorMessage::UndefinedProperty(self.method.lexeme.to_string()),
+            ));
+        };
+
+        let bound = gc::track(Rc::new(method.bind(instance)));
+        return Ok(LiteralValue::Function(bound));
+    }
+}
+
+impl ExprInterpret for InterpolationExpr {
+    fn interpret(&self, environment: &Rc<RefCell<Environment>>) -> Result<LiteralValue, RuntimeError> {
+        let mut text = String::from(self.segments[0].as_ref());
+        for (expression, segment) in self.expressions.iter().zip(&self.segments[1..]) {
+            let value = expression.interpret(environment)?;
+            text.push_str(&value.to_string());
+            text.push_str(segment);
+        }
+        return Ok(LiteralValue::String(Rc::from(text)));
+    }
+}
+
+fn look_up_variable(
+    environment: &Rc<RefCell<Environment>>,
+    name: &Token,
+    local: Option<LocalSlot>,
+) -> Result<LiteralValue, RuntimeError> {
+    let Some(local) = local else {
+        return environment.borrow().get_global(name);
+    };
+    return environment
+        .borrow()
+        .get_at(local)
+        .ok_or_else(|| undefined_variable(name));
+}
+
+fn operand_label(operand: &Expr, value: &LiteralValue) -> Label {
+    return error::operand_label(operand.span(), value.type_name());
+}
+
 pub fn is_truthy(value: &LiteralValue) -> bool {
     match *value {
         LiteralValue::String(_) => return true,
         LiteralValue::Number(_) => return true,
         LiteralValue::Bool(bool) => bool,
+        LiteralValue::Function(_) => return true,
+        LiteralValue::Native(_) => return true,
+        LiteralValue::Class(_) => return true,
+        LiteralValue::Instance(_) => return true,
         LiteralValue::None => return false,
     }
 }
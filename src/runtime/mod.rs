@@ -1,4 +1,9 @@
-mod error;
+pub mod error;
 pub mod interpret;
 pub mod evaluate;
 pub mod environment;
+pub mod callable;
+pub mod function;
+pub mod class;
+pub mod instance;
+pub mod native;
@@ -0,0 +1,54 @@
+use std::rc::Rc;
+
+use crate::{gc, parsing::expr::LiteralValue};
+
+use super::{callable::LoxCallable, error::RuntimeError};
+
+/// A function implemented by the interpreter rather than in Lox.
+pub struct NativeFunction {
+    pub name: &'static str,
+    arity: usize,
+    function: fn(Vec<LiteralValue>) -> LiteralValue,
+}
+
+/// The functions every program starts with in its global scope.
+pub fn natives() -> Vec<NativeFunction> {
+    return vec![
+        NativeFunction {
+            name: "gc",
+            arity: 0,
+            function: collect_garbage,
+        },
+        NativeFunction {
+            name: "str",
+            arity: 1,
+            function: to_string,
+        },
+    ];
+}
+
+/// Runs the cycle collector and returns how many objects it freed.
+fn collect_garbage(_arguments: Vec<LiteralValue>) -> LiteralValue {
+    return LiteralValue::Number(gc::collect() as f64);
+}
+
+/// Returns the text `print` would show for the argument.
+fn to_string(arguments: Vec<LiteralValue>) -> LiteralValue {
+    return LiteralValue::String(Rc::from(arguments[0].to_string()));
+}
+
+impl LoxCallable for NativeFunction {
+    fn arity(&self) -> usize {
+        return self.arity;
+    }
+
+    fn call(&self, arguments: Vec<LiteralValue>) -> Result<LiteralValue, RuntimeError> {
+        return Ok((self.function)(arguments));
+    }
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
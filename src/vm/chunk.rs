@@ -0,0 +1,169 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::lexing::span::Span;
+
+use super::value::Value;
+
+/// A single bytecode instruction. Operands follow the opcode byte in the
+/// chunk; unless noted otherwise they are 16-bit big-endian indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OpCode {
+    /// Pushes the constant at the operand index.
+    Constant,
+    Nil,
+    True,
+    False,
+    Pop,
+    GetLocal,
+    SetLocal,
+    /// The operand of the global and property instructions is the index of
+    /// the name in the constant pool.
+    GetGlobal,
+    DefineGlobal,
+    SetGlobal,
+    GetUpvalue,
+    SetUpvalue,
+    GetProperty,
+    SetProperty,
+    GetSuper,
+    Equal,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Not,
+    Negate,
+    Print,
+    /// Replaces the value on top of the stack with the text `print` would
+    /// show for it.
+    Stringify,
+    /// Jump operands are unsigned distances from the end of the instruction.
+    Jump,
+    JumpIfFalse,
+    Loop,
+    /// Takes a single-byte argument count.
+    Call,
+    /// Takes the function's constant index, then an `is_local` byte and an
+    /// index for each variable the closure captures.
+    Closure,
+    CloseUpvalue,
+    Return,
+    Class,
+    Inherit,
+    Method,
+}
+
+impl OpCode {
+    const ALL: [OpCode; 38] = [
+        OpCode::Constant,
+        OpCode::Nil,
+        OpCode::True,
+        OpCode::False,
+        OpCode::Pop,
+        OpCode::GetLocal,
+        OpCode::SetLocal,
+        OpCode::GetGlobal,
+        OpCode::DefineGlobal,
+        OpCode::SetGlobal,
+        OpCode::GetUpvalue,
+        OpCode::SetUpvalue,
+        OpCode::GetProperty,
+        OpCode::SetProperty,
+        OpCode::GetSuper,
+        OpCode::Equal,
+        OpCode::Greater,
+        OpCode::GreaterEqual,
+        OpCode::Less,
+        OpCode::LessEqual,
+        OpCode::Add,
+        OpCode::Subtract,
+        OpCode::Multiply,
+        OpCode::Divide,
+        OpCode::Not,
+        OpCode::Negate,
+        OpCode::Print,
+        OpCode::Stringify,
+        OpCode::Jump,
+        OpCode::JumpIfFalse,
+        OpCode::Loop,
+        OpCode::Call,
+        OpCode::Closure,
+        OpCode::CloseUpvalue,
+        OpCode::Return,
+        OpCode::Class,
+        OpCode::Inherit,
+        OpCode::Method,
+    ];
+
+    pub fn from_byte(byte: u8) -> Option<OpCode> {
+        return OpCode::ALL.get(byte as usize).copied();
+    }
+}
+
+/// A compiled function body: its bytecode, the constants it refers to, and
+/// the source span of every byte for error reporting.
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<u8>,
+    pub constants: Vec<Value>,
+    pub spans: Vec<Span>,
+    /// The operand spans of the instructions that label them in their
+    /// errors, keyed by the instruction's offset.
+    pub operand_spans: HashMap<usize, Vec<Span>>,
+    constant_indices: HashMap<ConstantKey, usize>,
+}
+
+/// The constants that are deduplicated in the pool.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum ConstantKey {
+    String(Rc<str>),
+    Number(u64),
+}
+
+impl Chunk {
+    pub fn write(&mut self, byte: u8, span: Span) {
+        self.code.push(byte);
+        self.spans.push(span);
+    }
+
+    pub fn write_u16(&mut self, value: u16, span: Span) {
+        let [high, low] = value.to_be_bytes();
+        self.write(high, span);
+        self.write(low, span);
+    }
+
+    pub fn read_u16(&self, offset: usize) -> u16 {
+        return u16::from_be_bytes([self.code[offset], self.code[offset + 1]]);
+    }
+
+    pub fn patch_u16(&mut self, offset: usize, value: u16) {
+        let [high, low] = value.to_be_bytes();
+        self.code[offset] = high;
+        self.code[offset + 1] = low;
+    }
+
+    /// Adds `value` to the constant pool, reusing an equal string or number
+    /// constant if there already is one.
+    pub fn add_constant(&mut self, value: Value) -> usize {
+        let key = match &value {
+            Value::String(string) => Some(ConstantKey::String(string.clone())),
+            Value::Number(number) => Some(ConstantKey::Number(number.to_bits())),
+            _ => None,
+        };
+        if let Some(index) = key.as_ref().and_then(|key| self.constant_indices.get(key)) {
+            return *index;
+        }
+
+        self.constants.push(value);
+        let index = self.constants.len() - 1;
+        if let Some(key) = key {
+            self.constant_indices.insert(key, index);
+        }
+        return index;
+    }
+}
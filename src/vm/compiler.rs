@@ -0,0 +1,674 @@
+use std::rc::Rc;
+
+use crate::{
+    lexing::{span::Span, token::TokenType},
+    parsing::{
+        expr::{
+            AssignExpr, BinaryExpr, CallExpr, Expr, GetExpr, GroupingExpr, InterpolationExpr,
+            LiteralExpr, LiteralValue, LogicalExpr, SetExpr, SuperExpr, ThisExpr, UnaryExpr,
+            VariableExpr,
+        },
+        stmt::{
+            BlockStmt, ClassStmt, ExpressionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt,
+            Stmt, VarStmt, WhileStmt,
+        },
+    },
+};
+
+use super::{
+    chunk::{Chunk, OpCode},
+    error::{CompileError, CompileErrorMessage},
+    object::Function,
+    value::Value,
+};
+
+/// The largest constant index, stack slot, upvalue index or jump distance an
+/// instruction operand can hold.
+const MAX_OPERAND: usize = u16::MAX as usize;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    Script,
+    Function,
+    Method,
+    Initializer,
+}
+
+struct Local {
+    name: String,
+    depth: usize,
+    is_captured: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct UpvalueRef {
+    index: u16,
+    is_local: bool,
+}
+
+/// The function currently being compiled along with the locals and captured
+/// variables it can see.
+struct FunctionState {
+    function: Function,
+    function_type: FunctionType,
+    locals: Vec<Local>,
+    upvalues: Vec<UpvalueRef>,
+    scope_depth: usize,
+}
+
+impl FunctionState {
+    fn new(name: String, function_type: FunctionType) -> Self {
+        // Slot zero holds the function being called, or the receiver in methods.
+        let receiver = match function_type {
+            FunctionType::Method | FunctionType::Initializer => "this",
+            FunctionType::Script | FunctionType::Function => "",
+        };
+        Self {
+            function: Function {
+                name,
+                ..Function::default()
+            },
+            function_type,
+            locals: vec![Local {
+                name: String::from(receiver),
+                depth: 0,
+                is_captured: false,
+            }],
+            upvalues: vec![],
+            scope_depth: 0,
+        }
+    }
+}
+
+/// Translates a resolved program into bytecode for the virtual machine. Locals
+/// live in stack slots and variables captured by closures become upvalues;
+/// only globals are looked up by name at runtime.
+pub struct Compiler {
+    states: Vec<FunctionState>,
+    errors: Vec<CompileError>,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            states: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub fn compile(mut self, statements: &[Stmt]) -> Result<Rc<Function>, Vec<CompileError>> {
+        self.states
+            .push(FunctionState::new(String::new(), FunctionType::Script));
+        for statement in statements {
+            statement.compile(&mut self);
+        }
+        let span = statements.last().map(Stmt::span).unwrap_or_default();
+        let (function, _) = self.end_function(span);
+
+        if self.errors.is_empty() {
+            return Ok(Rc::new(function));
+        }
+        return Err(self.errors);
+    }
+
+    fn current(&mut self) -> &mut FunctionState {
+        return self
+            .states
+            .last_mut()
+            .expect("No function is being compiled");
+    }
+
+    fn chunk(&mut self) -> &mut Chunk {
+        return &mut self.current().function.chunk;
+    }
+
+    fn error(&mut self, span: Span, message: CompileErrorMessage) {
+        self.errors.push(CompileError { span, message });
+    }
+
+    fn emit(&mut self, op: OpCode, span: Span) {
+        self.chunk().write(op as u8, span);
+    }
+
+    fn emit_with_operand(&mut self, op: OpCode, operand: u16, span: Span) {
+        self.emit(op, span);
+        self.chunk().write_u16(operand, span);
+    }
+
+    /// Emits `op` and remembers the spans of its operands so that a runtime
+    /// error can point at them.
+    fn emit_with_operand_spans(&mut self, op: OpCode, span: Span, operands: Vec<Span>) {
+        let offset = self.chunk().code.len();
+        self.chunk().operand_spans.insert(offset, operands);
+        self.emit(op, span);
+    }
+
+    fn make_constant(&mut self, value: Value, span: Span) -> u16 {
+        let index = self.chunk().add_constant(value);
+        if index > MAX_OPERAND {
+            self.error(span, CompileErrorMessage::TooManyConstants);
+            return 0;
+        }
+        return index as u16;
+    }
+
+    fn identifier_constant(&mut self, name: &str, span: Span) -> u16 {
+        return self.make_constant(Value::String(Rc::from(name)), span);
+    }
+
+    fn emit_constant(&mut self, value: Value, span: Span) {
+        let index = self.make_constant(value, span);
+        self.emit_with_operand(OpCode::Constant, index, span);
+    }
+
+    /// Emits a forward jump whose distance is filled in by `patch_jump`.
+    fn emit_jump(&mut self, op: OpCode, span: Span) -> usize {
+        self.emit_with_operand(op, u16::MAX, span);
+        return self.chunk().code.len() - 2;
+    }
+
+    fn patch_jump(&mut self, offset: usize, span: Span) {
+        let distance = self.chunk().code.len() - offset - 2;
+        if distance > MAX_OPERAND {
+            self.error(span, CompileErrorMessage::JumpTooLarge);
+            return;
+        }
+        self.chunk().patch_u16(offset, distance as u16);
+    }
+
+    fn emit_loop(&mut self, loop_start: usize, span: Span) {
+        let distance = self.chunk().code.len() + 3 - loop_start;
+        if distance > MAX_OPERAND {
+            self.error(span, CompileErrorMessage::JumpTooLarge);
+        }
+        self.emit_with_operand(OpCode::Loop, distance.min(MAX_OPERAND) as u16, span);
+    }
+
+    fn emit_return(&mut self, span: Span) {
+        if self.current().function_type == FunctionType::Initializer {
+            self.emit_with_operand(OpCode::GetLocal, 0, span);
+        } else {
+            self.emit(OpCode::Nil, span);
+        }
+        self.emit(OpCode::Return, span);
+    }
+
+    fn begin_scope(&mut self) {
+        self.current().scope_depth += 1;
+    }
+
+    fn end_scope(&mut self, span: Span) {
+        self.current().scope_depth -= 1;
+        loop {
+            let state = self.current();
+            let Some(local) = state.locals.last() else {
+                break;
+            };
+            if local.depth <= state.scope_depth {
+                break;
+            }
+            let op = if local.is_captured {
+                OpCode::CloseUpvalue
+            } else {
+                OpCode::Pop
+            };
+            state.locals.pop();
+            self.emit(op, span);
+        }
+    }
+
+    fn add_local(&mut self, name: &str, span: Span) {
+        if self.current().locals.len() > MAX_OPERAND {
+            self.error(span, CompileErrorMessage::TooManyLocals);
+            return;
+        }
+        let state = self.current();
+        let depth = state.scope_depth;
+        state.locals.push(Local {
+            name: String::from(name),
+            depth,
+            is_captured: false,
+        });
+    }
+
+    /// Binds a just-computed value on top of the stack to `name`: as a local
+    /// inside a scope, or as a global at the top level.
+    fn define_variable(&mut self, name: &str, span: Span) {
+        if self.current().scope_depth > 0 {
+            self.add_local(name, span);
+            return;
+        }
+        let index = self.identifier_constant(name, span);
+        self.emit_with_operand(OpCode::DefineGlobal, index, span);
+    }
+
+    fn resolve_local(&self, state: usize, name: &str) -> Option<u16> {
+        return self.states[state]
+            .locals
+            .iter()
+            .rposition(|local| local.name == name)
+            .map(|slot| slot as u16);
+    }
+
+    fn resolve_upvalue(&mut self, state: usize, name: &str, span: Span) -> Option<u16> {
+        if state == 0 {
+            return None;
+        }
+        if let Some(slot) = self.resolve_local(state - 1, name) {
+            self.states[state - 1].locals[slot as usize].is_captured = true;
+            return Some(self.add_upvalue(state, slot, true, span));
+        }
+        let index = self.resolve_upvalue(state - 1, name, span)?;
+        return Some(self.add_upvalue(state, index, false, span));
+    }
+
+    fn add_upvalue(&mut self, state: usize, index: u16, is_local: bool, span: Span) -> u16 {
+        let upvalue = UpvalueRef { index, is_local };
+        let upvalues = &self.states[state].upvalues;
+        if let Some(existing) = upvalues.iter().position(|other| *other == upvalue) {
+            return existing as u16;
+        }
+        if upvalues.len() > MAX_OPERAND {
+            self.error(span, CompileErrorMessage::TooManyUpvalues);
+            return 0;
+        }
+
+        let state = &mut self.states[state];
+        state.upvalues.push(upvalue);
+        state.function.upvalue_count = state.upvalues.len();
+        return (state.upvalues.len() - 1) as u16;
+    }
+
+    /// The instructions and operand that read and write `name` from the
+    /// current function.
+    fn variable(&mut self, name: &str, span: Span) -> (OpCode, OpCode, u16) {
+        let state = self.states.len() - 1;
+        if let Some(slot) = self.resolve_local(state, name) {
+            return (OpCode::GetLocal, OpCode::SetLocal, slot);
+        }
+        if let Some(index) = self.resolve_upvalue(state, name, span) {
+            return (OpCode::GetUpvalue, OpCode::SetUpvalue, index);
+        }
+        let index = self.identifier_constant(name, span);
+        return (OpCode::GetGlobal, OpCode::SetGlobal, index);
+    }
+
+    fn get_variable(&mut self, name: &str, span: Span) {
+        let (get, _, operand) = self.variable(name, span);
+        self.emit_with_operand(get, operand, span);
+    }
+
+    fn set_variable(&mut self, name: &str, span: Span) {
+        let (_, set, operand) = self.variable(name, span);
+        self.emit_with_operand(set, operand, span);
+    }
+
+    /// Compiles `declaration` as a new function and emits the instruction that
+    /// wraps it in a closure.
+    fn function(&mut self, declaration: &FunctionStmt, function_type: FunctionType) {
+        let name = declaration.name.lexeme.to_string();
+        self.states.push(FunctionState::new(name, function_type));
+        self.begin_scope();
+        for param in &declaration.params {
+            self.add_local(&param.lexeme, param.span);
+        }
+        self.current().function.arity = declaration.params.len();
+        for statement in &declaration.body {
+            statement.compile(self);
+        }
+        let (function, upvalues) = self.end_function(declaration.span);
+
+        let span = declaration.span;
+        let index = self.make_constant(Value::Function(Rc::new(function)), span);
+        self.emit_with_operand(OpCode::Closure, index, span);
+        for upvalue in upvalues {
+            self.chunk().write(upvalue.is_local as u8, span);
+            self.chunk().write_u16(upvalue.index, span);
+        }
+    }
+
+    fn end_function(&mut self, span: Span) -> (Function, Vec<UpvalueRef>) {
+        self.emit_return(span);
+        let state = self.states.pop().expect("No function is being compiled");
+        return (state.function, state.upvalues);
+    }
+}
+
+trait Compile {
+    fn compile(&self, compiler: &mut Compiler);
+}
+
+impl Compile for Stmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        match self {
+            Stmt::BlockStmt(stmt) => stmt.compile(compiler),
+            Stmt::ExpressionStmt(stmt) => stmt.compile(compiler),
+            Stmt::PrintStmt(stmt) => stmt.compile(compiler),
+            Stmt::VarStmt(stmt) => stmt.compile(compiler),
+            Stmt::IfStmt(stmt) => stmt.compile(compiler),
+            Stmt::WhileStmt(stmt) => stmt.compile(compiler),
+            Stmt::FunctionStmt(stmt) => stmt.compile(compiler),
+            Stmt::ReturnStmt(stmt) => stmt.compile(compiler),
+            Stmt::ClassStmt(stmt) => stmt.compile(compiler),
+        }
+    }
+}
+
+impl Compile for BlockStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.begin_scope();
+        for statement in &self.statements {
+            statement.compile(compiler);
+        }
+        compiler.end_scope(self.span);
+    }
+}
+
+impl Compile for ExpressionStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.expression.compile(compiler);
+        compiler.emit(OpCode::Pop, self.span);
+    }
+}
+
+impl Compile for PrintStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.expression.compile(compiler);
+        compiler.emit(OpCode::Print, self.span);
+    }
+}
+
+impl Compile for VarStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        match &self.initializer {
+            Some(initializer) => initializer.compile(compiler),
+            None => compiler.emit(OpCode::Nil, self.span),
+        }
+        compiler.define_variable(&self.name.lexeme, self.name.span);
+    }
+}
+
+impl Compile for IfStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.condition.compile(compiler);
+        let then_jump = compiler.emit_jump(OpCode::JumpIfFalse, self.span);
+        compiler.emit(OpCode::Pop, self.span);
+        self.then_branch.compile(compiler);
+
+        let else_jump = compiler.emit_jump(OpCode::Jump, self.span);
+        compiler.patch_jump(then_jump, self.span);
+        compiler.emit(OpCode::Pop, self.span);
+        if let Some(else_branch) = &self.else_branch {
+            else_branch.compile(compiler);
+        }
+        compiler.patch_jump(else_jump, self.span);
+    }
+}
+
+impl Compile for WhileStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        let loop_start = compiler.chunk().code.len();
+        self.condition.compile(compiler);
+        let exit_jump = compiler.emit_jump(OpCode::JumpIfFalse, self.span);
+        compiler.emit(OpCode::Pop, self.span);
+        self.body.compile(compiler);
+        compiler.emit_loop(loop_start, self.span);
+
+        compiler.patch_jump(exit_jump, self.span);
+        compiler.emit(OpCode::Pop, self.span);
+    }
+}
+
+impl Compile for Rc<FunctionStmt> {
+    fn compile(&self, compiler: &mut Compiler) {
+        let name = &self.name;
+        if compiler.current().scope_depth > 0 {
+            // Declared before the body is compiled so that it can call itself.
+            compiler.add_local(&name.lexeme, name.span);
+            compiler.function(self, FunctionType::Function);
+            return;
+        }
+        compiler.function(self, FunctionType::Function);
+        compiler.define_variable(&name.lexeme, name.span);
+    }
+}
+
+impl Compile for ReturnStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        match &self.value {
+            Some(value) => {
+                value.compile(compiler);
+                compiler.emit(OpCode::Return, self.span);
+            }
+            None => compiler.emit_return(self.span),
+        }
+    }
+}
+
+impl Compile for ClassStmt {
+    fn compile(&self, compiler: &mut Compiler) {
+        let name = &self.name;
+        let name_constant = compiler.identifier_constant(&name.lexeme, name.span);
+        let is_local = compiler.current().scope_depth > 0;
+        if is_local {
+            compiler.add_local(&name.lexeme, name.span);
+        }
+        compiler.emit_with_operand(OpCode::Class, name_constant, name.span);
+        if !is_local {
+            compiler.emit_with_operand(OpCode::DefineGlobal, name_constant, name.span);
+        }
+
+        if let Some(superclass) = &self.superclass {
+            superclass.compile(compiler);
+            compiler.begin_scope();
+            compiler.add_local("super", superclass.span);
+            compiler.get_variable(&name.lexeme, name.span);
+            compiler.emit(OpCode::Inherit, superclass.name.span);
+        }
+
+        compiler.get_variable(&name.lexeme, name.span);
+        for method in &self.methods {
+            let function_type = if method.name.lexeme.as_ref() == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            compiler.function(method, function_type);
+            let method_constant = compiler.identifier_constant(&method.name.lexeme, method.span);
+            compiler.emit_with_operand(OpCode::Method, method_constant, method.name.span);
+        }
+        compiler.emit(OpCode::Pop, self.span);
+
+        if self.superclass.is_some() {
+            compiler.end_scope(self.span);
+        }
+    }
+}
+
+impl Compile for Expr {
+    fn compile(&self, compiler: &mut Compiler) {
+        match self {
+            Expr::AssignExpr(expr) => expr.compile(compiler),
+            Expr::UnaryExpr(expr) => expr.compile(compiler),
+            Expr::LiteralExpr(expr) => expr.compile(compiler),
+            Expr::GroupingExpr(expr) => expr.compile(compiler),
+            Expr::BinaryExpr(expr) => expr.compile(compiler),
+            Expr::VariableExpr(expr) => expr.compile(compiler),
+            Expr::LogicalExpr(expr) => expr.compile(compiler),
+            Expr::CallExpr(expr) => expr.compile(compiler),
+            Expr::GetExpr(expr) => expr.compile(compiler),
+            Expr::SetExpr(expr) => expr.compile(compiler),
+            Expr::ThisExpr(expr) => expr.compile(compiler),
+            Expr::SuperExpr(expr) => expr.compile(compiler),
+            Expr::InterpolationExpr(expr) => expr.compile(compiler),
+        }
+    }
+}
+
+impl Compile for AssignExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.value.compile(compiler);
+        compiler.set_variable(&self.name.lexeme, self.name.span);
+    }
+}
+
+impl Compile for LiteralExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        match &self.value {
+            LiteralValue::Number(number) => {
+                compiler.emit_constant(Value::Number(*number), self.span)
+            }
+            LiteralValue::String(string) => {
+                compiler.emit_constant(Value::String(string.clone()), self.span)
+            }
+            LiteralValue::Bool(true) => compiler.emit(OpCode::True, self.span),
+            LiteralValue::Bool(false) => compiler.emit(OpCode::False, self.span),
+            LiteralValue::None => compiler.emit(OpCode::Nil, self.span),
+            LiteralValue::Function(_)
+            | LiteralValue::Native(_)
+            | LiteralValue::Class(_)
+            | LiteralValue::Instance(_) => {
+                unreachable!("The parser only produces literals of primitive values")
+            }
+        }
+    }
+}
+
+impl Compile for UnaryExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.right.compile(compiler);
+        let span = self.operator.span;
+        match self.operator.token_type {
+            TokenType::Minus => {
+                compiler.emit_with_operand_spans(OpCode::Negate, span, vec![self.right.span()])
+            }
+            TokenType::Bang => compiler.emit(OpCode::Not, span),
+            _ => unreachable!("Invalid unary operator"),
+        }
+    }
+}
+
+impl Compile for GroupingExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.expression.compile(compiler);
+    }
+}
+
+impl Compile for BinaryExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.left.compile(compiler);
+        self.right.compile(compiler);
+
+        let span = self.operator.span;
+        let op = match self.operator.token_type {
+            TokenType::EqualEqual => OpCode::Equal,
+            TokenType::BangEqual => {
+                compiler.emit(OpCode::Equal, span);
+                compiler.emit(OpCode::Not, span);
+                return;
+            }
+            TokenType::Plus => OpCode::Add,
+            TokenType::Minus => OpCode::Subtract,
+            TokenType::Star => OpCode::Multiply,
+            TokenType::Slash => OpCode::Divide,
+            TokenType::Greater => OpCode::Greater,
+            TokenType::GreaterEqual => OpCode::GreaterEqual,
+            TokenType::Less => OpCode::Less,
+            TokenType::LessEqual => OpCode::LessEqual,
+            _ => unreachable!("Invalid binary operator"),
+        };
+        let operands = vec![self.left.span(), self.right.span()];
+        compiler.emit_with_operand_spans(op, span, operands);
+    }
+}
+
+/// Builds the string by appending each value, stringified, and each segment
+/// after it to the first segment.
+impl Compile for InterpolationExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.emit_constant(Value::String(self.segments[0].clone()), self.span);
+        for (expression, segment) in self.expressions.iter().zip(&self.segments[1..]) {
+            expression.compile(compiler);
+            let span = expression.span();
+            compiler.emit(OpCode::Stringify, span);
+            compiler.emit(OpCode::Add, span);
+            if !segment.is_empty() {
+                compiler.emit_constant(Value::String(segment.clone()), self.span);
+                compiler.emit(OpCode::Add, self.span);
+            }
+        }
+    }
+}
+
+impl Compile for VariableExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.get_variable(&self.name.lexeme, self.name.span);
+    }
+}
+
+impl Compile for LogicalExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.left.compile(compiler);
+        let span = self.operator.span;
+        if self.operator.token_type == TokenType::Or {
+            let else_jump = compiler.emit_jump(OpCode::JumpIfFalse, span);
+            let end_jump = compiler.emit_jump(OpCode::Jump, span);
+            compiler.patch_jump(else_jump, span);
+            compiler.emit(OpCode::Pop, span);
+            self.right.compile(compiler);
+            compiler.patch_jump(end_jump, span);
+            return;
+        }
+
+        let end_jump = compiler.emit_jump(OpCode::JumpIfFalse, span);
+        compiler.emit(OpCode::Pop, span);
+        self.right.compile(compiler);
+        compiler.patch_jump(end_jump, span);
+    }
+}
+
+impl Compile for CallExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.callee.compile(compiler);
+        for argument in &self.arguments {
+            argument.compile(compiler);
+        }
+        // The parser caps argument lists at 255.
+        compiler.emit(OpCode::Call, self.paren.span);
+        compiler
+            .chunk()
+            .write(self.arguments.len() as u8, self.paren.span);
+    }
+}
+
+impl Compile for GetExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.object.compile(compiler);
+        let name = compiler.identifier_constant(&self.name.lexeme, self.name.span);
+        compiler.emit_with_operand(OpCode::GetProperty, name, self.name.span);
+    }
+}
+
+impl Compile for SetExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        self.object.compile(compiler);
+        self.value.compile(compiler);
+        let name = compiler.identifier_constant(&self.name.lexeme, self.name.span);
+        compiler.emit_with_operand(OpCode::SetProperty, name, self.name.span);
+    }
+}
+
+impl Compile for ThisExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.get_variable("this", self.keyword.span);
+    }
+}
+
+impl Compile for SuperExpr {
+    fn compile(&self, compiler: &mut Compiler) {
+        compiler.get_variable("this", self.keyword.span);
+        compiler.get_variable("super", self.keyword.span);
+        let name = compiler.identifier_constant(&self.method.lexeme, self.method.span);
+        compiler.emit_with_operand(OpCode::GetSuper, name, self.method.span);
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt::Write;
+
+use super::{
+    chunk::{Chunk, OpCode},
+    object::Function,
+    value::Value,
+};
+
+/// Renders the bytecode of `function`, followed by that of every function
+/// nested in it, one instruction per line.
+pub fn disassemble(function: &Function) -> String {
+    let mut output = String::new();
+    disassemble_function(&mut output, function);
+    return output;
+}
+
+fn disassemble_function(output: &mut String, function: &Function) {
+    let chunk = &function.chunk;
+    let _ = writeln!(output, "== {:?} ==", function);
+
+    let mut offset = 0;
+    while offset < chunk.code.len() {
+        offset = disassemble_instruction(output, chunk, offset);
+    }
+
+    for constant in &chunk.constants {
+        if let Value::Function(nested) = constant {
+            output.push('\n');
+            disassemble_function(output, nested);
+        }
+    }
+}
+
+/// Writes the instruction at `offset` and returns the offset of the next one.
+fn disassemble_instruction(output: &mut String, chunk: &Chunk, offset: usize) -> usize {
+    let line = chunk.spans[offset].line;
+    let same_line = offset > 0 && chunk.spans[offset - 1].line == line;
+    if same_line {
+        let _ = write!(output, "{:04}    | ", offset);
+    } else {
+        let _ = write!(output, "{:04} {:>4} ", offset, line);
+    }
+
+    let Some(op) = OpCode::from_byte(chunk.code[offset]) else {
+        let _ = writeln!(output, "Unknown opcode {}", chunk.code[offset]);
+        return offset + 1;
+    };
+    let name = format!("{:?}", op);
+
+    match op {
+        OpCode::Constant
+        | OpCode::GetGlobal
+        | OpCode::DefineGlobal
+        | OpCode::SetGlobal
+        | OpCode::GetProperty
+        | OpCode::SetProperty
+        | OpCode::GetSuper
+        | OpCode::Class
+        | OpCode::Method => {
+            let index = chunk.read_u16(offset + 1);
+            let constant = &chunk.constants[index as usize];
+            let _ = writeln!(output, "{:<16} {:>5} {:?}", name, index, constant);
+            return offset + 3;
+        }
+        OpCode::GetLocal | OpCode::SetLocal | OpCode::GetUpvalue | OpCode::SetUpvalue => {
+            let slot = chunk.read_u16(offset + 1);
+            let _ = writeln!(output, "{:<16} {:>5}", name, slot);
+            return offset + 3;
+        }
+        OpCode::Jump | OpCode::JumpIfFalse | OpCode::Loop => {
+            let distance = chunk.read_u16(offset + 1) as usize;
+            let target = if op == OpCode::Loop {
+                offset + 3 - distance
+            } else {
+                offset + 3 + distance
+            };
+            let _ = writeln!(output, "{:<16} {:>5} -> {:04}", name, distance, target);
+            return offset + 3;
+        }
+        OpCode::Call => {
+            let arg_count = chunk.code[offset + 1];
+            let _ = writeln!(output, "{:<16} {:>5}", name, arg_count);
+            return offset + 2;
+        }
+        OpCode::Closure => {
+            let index = chunk.read_u16(offset + 1);
+            let constant = &chunk.constants[index as usize];
+            let _ = writeln!(output, "{:<16} {:>5} {:?}", name, index, constant);
+
+            let mut offset = offset + 3;
+            let upvalue_count = match constant {
+                Value::Function(function) => function.upvalue_count,
+                _ => 0,
+            };
+            for _ in 0..upvalue_count {
+                let kind = if chunk.code[offset] == 1 {
+                    "local"
+                } else {
+                    "upvalue"
+                };
+                let index = chunk.read_u16(offset + 1);
+                let _ = writeln!(
+                    output,
+                    "{:04}    |                     {} {}",
+                    offset, kind, index
+                );
+                offset += 3;
+            }
+            return offset;
+        }
+        OpCode::Nil
+        | OpCode::True
+        | OpCode::False
+        | OpCode::Pop
+        | OpCode::Equal
+        | OpCode::Greater
+        | OpCode::GreaterEqual
+        | OpCode::Less
+        | OpCode::LessEqual
+        | OpCode::Add
+        | OpCode::Subtract
+        | OpCode::Multiply
+        | OpCode::Divide
+        | OpCode::Not
+        | OpCode::Negate
+        | OpCode::Print
+        | OpCode::Stringify
+        | OpCode::CloseUpvalue
+        | OpCode::Return
+        | OpCode::Inherit => {
+            let _ = writeln!(output, "{}", name);
+            return offset + 1;
+        }
+    }
+}
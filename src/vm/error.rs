@@ -0,0 +1,50 @@
+use crate::{error::Diagnostic, lexing::span::Span};
+
+/// Limits of the bytecode format that a script can run into while being
+/// compiled.
+#[derive(Debug, Clone, Copy)]
+pub enum CompileErrorMessage {
+    TooManyConstants,
+    TooManyLocals,
+    TooManyUpvalues,
+    JumpTooLarge,
+}
+
+impl std::fmt::Display for CompileErrorMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileErrorMessage::TooManyConstants => write!(f, "Too many constants in one chunk."),
+            CompileErrorMessage::TooManyLocals => {
+                write!(f, "Too many local variables in function.")
+            }
+            CompileErrorMessage::TooManyUpvalues => {
+                write!(f, "Too many closure variables in function.")
+            }
+            CompileErrorMessage::JumpTooLarge => write!(f, "Too much code to jump over."),
+        }
+    }
+}
+
+impl CompileErrorMessage {
+    /// The stable identifier tools can match this error on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CompileErrorMessage::TooManyConstants => "E0301",
+            CompileErrorMessage::TooManyLocals => "E0302",
+            CompileErrorMessage::TooManyUpvalues => "E0303",
+            CompileErrorMessage::JumpTooLarge => "E0304",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    pub span: Span,
+    pub message: CompileErrorMessage,
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(error: &CompileError) -> Self {
+        return Diagnostic::error(error.message.code(), error.message.to_string(), error.span);
+    }
+}
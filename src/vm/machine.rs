@@ -0,0 +1,529 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    gc,
+    lexing::span::Span,
+    runtime::{
+        callable::MAX_CALL_DEPTH,
+        error::{operand_label, RuntimeError, RuntimeErrorMessage},
+    },
+};
+
+use super::{
+    chunk::{Chunk, OpCode},
+    native::natives,
+    object::{BoundMethod, Class, Closure, Function, Instance, Upvalue},
+    value::Value,
+};
+
+/// How many calls may be active at once, the same as in the tree-walking
+/// backend.
+const FRAMES_MAX: usize = MAX_CALL_DEPTH;
+
+/// An active function call: the closure being run, the offset of its next
+/// instruction, and where its slots start on the stack.
+struct CallFrame {
+    closure: Rc<Closure>,
+    ip: usize,
+    base: usize,
+}
+
+impl CallFrame {
+    fn chunk(&self) -> &Chunk {
+        return &self.closure.function.chunk;
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let byte = self.closure.function.chunk.code[self.ip];
+        self.ip += 1;
+        return byte;
+    }
+
+    fn read_u16(&mut self) -> u16 {
+        let value = self.chunk().read_u16(self.ip);
+        self.ip += 2;
+        return value;
+    }
+
+    fn read_constant(&mut self) -> Value {
+        let index = self.read_u16() as usize;
+        return self.chunk().constants[index].clone();
+    }
+
+    fn read_name(&mut self) -> Rc<str> {
+        match self.read_constant() {
+            Value::String(name) => return name,
+            _ => unreachable!("Names are always string constants"),
+        }
+    }
+}
+
+/// A stack-based virtual machine that runs the bytecode produced by the
+/// compiler.
+pub struct Vm {
+    stack: Vec<Value>,
+    /// The callers of the function that is currently running.
+    frames: Vec<CallFrame>,
+    globals: HashMap<Rc<str>, Value>,
+    /// Upvalues that still point into the stack, ordered by slot.
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        let globals = natives()
+            .into_iter()
+            .map(|native| (Rc::from(native.name), Value::Native(Rc::new(native))))
+            .collect();
+        Self {
+            stack: vec![],
+            frames: vec![],
+            globals,
+            open_upvalues: vec![],
+        }
+    }
+
+    pub fn interpret(&mut self, function: Rc<Function>) -> Result<(), RuntimeError> {
+        let closure = gc::track(Rc::new(Closure {
+            function,
+            upvalues: vec![],
+        }));
+        self.stack.push(Value::Closure(closure.clone()));
+        let frame = CallFrame {
+            closure,
+            ip: 0,
+            base: 0,
+        };
+
+        let result = self.run(frame);
+        if result.is_err() {
+            self.stack.clear();
+            self.frames.clear();
+            self.open_upvalues.clear();
+        }
+        return result;
+    }
+
+    fn run(&mut self, mut frame: CallFrame) -> Result<(), RuntimeError> {
+        loop {
+            let offset = frame.ip;
+            let op = OpCode::from_byte(frame.read_byte()).expect("Invalid opcode");
+            match op {
+                OpCode::Constant => {
+                    let value = frame.read_constant();
+                    self.stack.push(value);
+                }
+                OpCode::Nil => self.stack.push(Value::Nil),
+                OpCode::True => self.stack.push(Value::Bool(true)),
+                OpCode::False => self.stack.push(Value::Bool(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetLocal => {
+                    let slot = frame.base + frame.read_u16() as usize;
+                    self.stack.push(self.stack[slot].clone());
+                }
+                OpCode::SetLocal => {
+                    let slot = frame.base + frame.read_u16() as usize;
+                    self.stack[slot] = self.peek(0).clone();
+                }
+                OpCode::GetGlobal => {
+                    let name = frame.read_name();
+                    let Some(value) = self.globals.get(&name) else {
+                        let message = RuntimeErrorMessage::UndefinedVariable(name.to_string());
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    self.stack.push(value.clone());
+                }
+                OpCode::DefineGlobal => {
+                    let name = frame.read_name();
+                    let value = self.pop();
+                    self.globals.insert(name, value);
+                }
+                OpCode::SetGlobal => {
+                    let name = frame.read_name();
+                    let value = self.peek(0).clone();
+                    let Some(slot) = self.globals.get_mut(&name) else {
+                        let message = RuntimeErrorMessage::UndefinedVariable(name.to_string());
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    *slot = value;
+                }
+                OpCode::GetUpvalue => {
+                    let index = frame.read_u16() as usize;
+                    let value = match &*frame.closure.upvalues[index].borrow() {
+                        Upvalue::Open(slot) => self.stack[*slot].clone(),
+                        Upvalue::Closed(value) => value.clone(),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::SetUpvalue => {
+                    let index = frame.read_u16() as usize;
+                    let value = self.peek(0).clone();
+                    match &mut *frame.closure.upvalues[index].borrow_mut() {
+                        Upvalue::Open(slot) => self.stack[*slot] = value,
+                        Upvalue::Closed(closed) => *closed = value,
+                    }
+                }
+                OpCode::GetProperty => {
+                    let name = frame.read_name();
+                    let Value::Instance(instance) = self.peek(0).clone() else {
+                        let message = RuntimeErrorMessage::OnlyInstancesHaveProperties;
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    let field = instance.fields.borrow().get(&*name).cloned();
+                    let value = match field {
+                        Some(value) => value,
+                        None => {
+                            let receiver = Value::Instance(instance.clone());
+                            let Some(method) = bind_method(&instance.class, &name, receiver) else {
+                                let message =
+                                    RuntimeErrorMessage::UndefinedProperty(name.to_string());
+                                return Err(self.error(&frame, offset, message));
+                            };
+                            method
+                        }
+                    };
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::SetProperty => {
+                    let name = frame.read_name();
+                    let Value::Instance(instance) = self.peek(1).clone() else {
+                        let message = RuntimeErrorMessage::OnlyInstancesHaveFields;
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    let value = self.pop();
+                    instance.fields.borrow_mut().insert(name, value.clone());
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::GetSuper => {
+                    let name = frame.read_name();
+                    let Value::Class(superclass) = self.pop() else {
+                        unreachable!("'super' is always bound to a class")
+                    };
+                    let receiver = self.pop();
+                    let Some(method) = bind_method(&superclass, &name, receiver) else {
+                        let message = RuntimeErrorMessage::UndefinedProperty(name.to_string());
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    self.stack.push(method);
+                }
+                OpCode::Equal => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(Value::Bool(left == right));
+                }
+                OpCode::Greater => self.compare(&frame, offset, |a, b| a > b)?,
+                OpCode::GreaterEqual => self.compare(&frame, offset, |a, b| a >= b)?,
+                OpCode::Less => self.compare(&frame, offset, |a, b| a < b)?,
+                OpCode::LessEqual => self.compare(&frame, offset, |a, b| a <= b)?,
+                OpCode::Add => {
+                    let result = match (self.peek(1), self.peek(0)) {
+                        (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+                        (Value::String(a), Value::String(b)) => {
+                            let mut result = String::with_capacity(a.len() + b.len());
+                            result.push_str(a);
+                            result.push_str(b);
+                            Value::String(Rc::from(result))
+                        }
+                        // A string turns the other operand into its text.
+                        (a @ Value::String(_), b) | (a, b @ Value::String(_)) => {
+                            Value::String(Rc::from(format!("{}{}", a, b)))
+                        }
+                        _ => {
+                            let message = RuntimeErrorMessage::OperandsMustBeNumberOrString;
+                            return Err(self.error(&frame, offset, message));
+                        }
+                    };
+                    self.replace_operands(2, result);
+                }
+                OpCode::Subtract => self.arithmetic(&frame, offset, |a, b| a - b)?,
+                OpCode::Multiply => self.arithmetic(&frame, offset, |a, b| a * b)?,
+                OpCode::Divide => self.arithmetic(&frame, offset, |a, b| a / b)?,
+                OpCode::Not => {
+                    let value = self.pop();
+                    self.stack.push(Value::Bool(value.is_falsey()));
+                }
+                OpCode::Negate => {
+                    let Value::Number(number) = self.peek(0) else {
+                        let message = RuntimeErrorMessage::OperandMustBeNumber;
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    let result = Value::Number(-number);
+                    self.replace_operands(1, result);
+                }
+                OpCode::Print => {
+                    let value = self.pop();
+                    println!("{}", value);
+                }
+                OpCode::Stringify => {
+                    let value = self.pop();
+                    self.stack.push(Value::String(Rc::from(value.to_string())));
+                }
+                OpCode::Jump => {
+                    let distance = frame.read_u16() as usize;
+                    frame.ip += distance;
+                }
+                OpCode::JumpIfFalse => {
+                    let distance = frame.read_u16() as usize;
+                    if self.peek(0).is_falsey() {
+                        frame.ip += distance;
+                    }
+                }
+                OpCode::Loop => {
+                    let distance = frame.read_u16() as usize;
+                    frame.ip -= distance;
+                }
+                OpCode::Call => {
+                    let arg_count = frame.read_byte() as usize;
+                    if let Some(callee) = self.call_value(&frame, offset, arg_count)? {
+                        self.frames.push(std::mem::replace(&mut frame, callee));
+                    }
+                }
+                OpCode::Closure => {
+                    let Value::Function(function) = frame.read_constant() else {
+                        unreachable!("Closures are always made from function constants")
+                    };
+                    let mut upvalues = Vec::with_capacity(function.upvalue_count);
+                    for _ in 0..function.upvalue_count {
+                        let is_local = frame.read_byte() == 1;
+                        let index = frame.read_u16() as usize;
+                        if is_local {
+                            upvalues.push(self.capture_upvalue(frame.base + index));
+                        } else {
+                            upvalues.push(frame.closure.upvalues[index].clone());
+                        }
+                    }
+                    let closure = Closure { function, upvalues };
+                    self.stack.push(Value::Closure(gc::track(Rc::new(closure))));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    self.close_upvalues(frame.base);
+                    self.stack.truncate(frame.base);
+                    let Some(caller) = self.frames.pop() else {
+                        return Ok(());
+                    };
+                    frame = caller;
+                    self.stack.push(result);
+                }
+                OpCode::Class => {
+                    let name = frame.read_name();
+                    let class = Class::new(name);
+                    self.stack.push(Value::Class(gc::track(Rc::new(class))));
+                }
+                OpCode::Inherit => {
+                    let Value::Class(subclass) = self.peek(0).clone() else {
+                        unreachable!("Only classes inherit")
+                    };
+                    let Value::Class(superclass) = self.peek(1).clone() else {
+                        let message = RuntimeErrorMessage::SuperclassMustBeClass;
+                        return Err(self.error(&frame, offset, message));
+                    };
+                    if Rc::ptr_eq(&superclass, &subclass) {
+                        let message = RuntimeErrorMessage::InheritFromSelf;
+                        return Err(self.error(&frame, offset, message));
+                    }
+                    // Methods are copied down now since neither class can gain
+                    // methods later.
+                    let methods = superclass.methods.borrow().clone();
+                    subclass.methods.borrow_mut().extend(methods);
+                    self.pop();
+                }
+                OpCode::Method => {
+                    let name = frame.read_name();
+                    let Value::Closure(method) = self.pop() else {
+                        unreachable!("Methods are always closures")
+                    };
+                    let Value::Class(class) = self.peek(0) else {
+                        unreachable!("Methods are always defined on a class")
+                    };
+                    class.methods.borrow_mut().insert(name, method);
+                }
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        return self.stack.pop().expect("Stack underflow");
+    }
+
+    fn peek(&self, distance: usize) -> &Value {
+        return &self.stack[self.stack.len() - 1 - distance];
+    }
+
+    /// Pops the `count` operands of an instruction and pushes its result.
+    fn replace_operands(&mut self, count: usize, result: Value) {
+        self.stack.truncate(self.stack.len() - count);
+        self.stack.push(result);
+    }
+
+    fn number_operands(
+        &self,
+        frame: &CallFrame,
+        offset: usize,
+    ) -> Result<(f64, f64), RuntimeError> {
+        match (self.peek(1), self.peek(0)) {
+            (Value::Number(a), Value::Number(b)) => return Ok((*a, *b)),
+            _ => {
+                let message = RuntimeErrorMessage::OperandsMustBeNumbers;
+                return Err(self.error(frame, offset, message));
+            }
+        }
+    }
+
+    fn arithmetic(
+        &mut self,
+        frame: &CallFrame,
+        offset: usize,
+        op: fn(f64, f64) -> f64,
+    ) -> Result<(), RuntimeError> {
+        let (a, b) = self.number_operands(frame, offset)?;
+        self.replace_operands(2, Value::Number(op(a, b)));
+        return Ok(());
+    }
+
+    fn compare(
+        &mut self,
+        frame: &CallFrame,
+        offset: usize,
+        op: fn(f64, f64) -> bool,
+    ) -> Result<(), RuntimeError> {
+        let (a, b) = self.number_operands(frame, offset)?;
+        self.replace_operands(2, Value::Bool(op(a, b)));
+        return Ok(());
+    }
+
+    /// Calls the value below the top `arg_count` values on the stack. Returns
+    /// the frame to switch to, if the callee is a function that needs one.
+    fn call_value(
+        &mut self,
+        frame: &CallFrame,
+        offset: usize,
+        arg_count: usize,
+    ) -> Result<Option<CallFrame>, RuntimeError> {
+        let slot = self.stack.len() - arg_count - 1;
+        let closure = match self.stack[slot].clone() {
+            Value::Closure(closure) => closure,
+            Value::BoundMethod(bound) => {
+                self.stack[slot] = bound.receiver.clone();
+                bound.method.clone()
+            }
+            Value::Class(class) => {
+                let instance = gc::track(Rc::new(Instance::new(class.clone())));
+                self.stack[slot] = Value::Instance(instance);
+                let initializer = class.methods.borrow().get("init").cloned();
+                match initializer {
+                    Some(initializer) => initializer,
+                    None if arg_count == 0 => return Ok(None),
+                    None => {
+                        let message = RuntimeErrorMessage::WrongArity {
+                            expected: 0,
+                            got: arg_count,
+                        };
+                        return Err(self.error(frame, offset, message));
+                    }
+                }
+            }
+            Value::Native(native) => {
+                if arg_count != native.arity {
+                    let message = RuntimeErrorMessage::WrongArity {
+                        expected: native.arity,
+                        got: arg_count,
+                    };
+                    return Err(self.error(frame, offset, message));
+                }
+                let result = (native.function)(&self.stack[slot + 1..]);
+                self.stack.truncate(slot);
+                self.stack.push(result);
+                return Ok(None);
+            }
+            _ => return Err(self.error(frame, offset, RuntimeErrorMessage::NotCallable)),
+        };
+
+        if arg_count != closure.function.arity {
+            let message = RuntimeErrorMessage::WrongArity {
+                expected: closure.function.arity,
+                got: arg_count,
+            };
+            return Err(self.error(frame, offset, message));
+        }
+        if self.frames.len() >= FRAMES_MAX {
+            return Err(self.error(frame, offset, RuntimeErrorMessage::StackOverflow));
+        }
+        return Ok(Some(CallFrame {
+            closure,
+            ip: 0,
+            base: slot,
+        }));
+    }
+
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let position = self
+            .open_upvalues
+            .partition_point(|upvalue| open_slot(upvalue) < slot);
+        if let Some(existing) = self.open_upvalues.get(position) {
+            if open_slot(existing) == slot {
+                return existing.clone();
+            }
+        }
+
+        let upvalue = gc::track(Rc::new(RefCell::new(Upvalue::Open(slot))));
+        self.open_upvalues.insert(position, upvalue.clone());
+        return upvalue;
+    }
+
+    /// Moves the values of every open upvalue at or above `slot` off the stack.
+    fn close_upvalues(&mut self, slot: usize) {
+        let position = self
+            .open_upvalues
+            .partition_point(|upvalue| open_slot(upvalue) < slot);
+        for upvalue in self.open_upvalues.drain(position..) {
+            let value = self.stack[open_slot(&upvalue)].clone();
+            *upvalue.borrow_mut() = Upvalue::Closed(value);
+        }
+    }
+
+    /// Builds the error for the instruction at `offset`. Instructions that
+    /// recorded operand spans have their operands, still on top of the stack,
+    /// labeled with their types.
+    fn error(
+        &self,
+        frame: &CallFrame,
+        offset: usize,
+        message: RuntimeErrorMessage,
+    ) -> RuntimeError {
+        let chunk = frame.chunk();
+        let error = RuntimeError::at(chunk.spans[offset], message);
+        let Some(spans) = chunk.operand_spans.get(&offset) else {
+            return error;
+        };
+
+        let operands = &self.stack[self.stack.len() - spans.len()..];
+        let labels = spans
+            .iter()
+            .zip(operands)
+            .map(|(span, operand): (&Span, &Value)| operand_label(*span, operand.type_name()))
+            .collect();
+        return error.with_labels(labels);
+    }
+}
+
+fn open_slot(upvalue: &Rc<RefCell<Upvalue>>) -> usize {
+    match &*upvalue.borrow() {
+        Upvalue::Open(slot) => return *slot,
+        Upvalue::Closed(_) => unreachable!("Only open upvalues are tracked"),
+    }
+}
+
+fn bind_method(class: &Class, name: &str, receiver: Value) -> Option<Value> {
+    let method = class.methods.borrow().get(name)?.clone();
+    let bound = BoundMethod { receiver, method };
+    return Some(Value::BoundMethod(gc::track(Rc::new(bound))));
+}
@@ -0,0 +1,8 @@
+pub mod chunk;
+pub mod compiler;
+pub mod disassemble;
+pub mod error;
+pub mod machine;
+pub mod native;
+pub mod object;
+pub mod value;
@@ -0,0 +1,45 @@
+use std::rc::Rc;
+
+use crate::gc;
+
+use super::value::Value;
+
+/// A function implemented by the virtual machine rather than in Lox.
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(&[Value]) -> Value,
+}
+
+/// The functions every program starts with as globals, matching those of the
+/// tree-walking backend.
+pub fn natives() -> Vec<NativeFunction> {
+    return vec![
+        NativeFunction {
+            name: "gc",
+            arity: 0,
+            function: collect_garbage,
+        },
+        NativeFunction {
+            name: "str",
+            arity: 1,
+            function: to_string,
+        },
+    ];
+}
+
+/// Runs the cycle collector and returns how many objects it freed.
+fn collect_garbage(_arguments: &[Value]) -> Value {
+    return Value::Number(gc::collect() as f64);
+}
+
+/// Returns the text `print` would show for the argument.
+fn to_string(arguments: &[Value]) -> Value {
+    return Value::String(Rc::from(arguments[0].to_string()));
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
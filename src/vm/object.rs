@@ -0,0 +1,143 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::gc::{self, Address, Trace};
+
+use super::{chunk::Chunk, value::Value};
+
+#[derive(Default)]
+pub struct Function {
+    pub name: String,
+    pub arity: usize,
+    pub upvalue_count: usize,
+    pub chunk: Chunk,
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            return write!(f, "<script>");
+        }
+        write!(f, "<fn {}>", self.name)
+    }
+}
+
+/// A variable captured by a closure. It points into the stack while the
+/// variable's scope is active and holds the value itself once it has ended.
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
+}
+
+pub struct Closure {
+    pub function: Rc<Function>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+/// A class. Its name and the names of its methods are the string constants
+/// the compiler emitted for them, shared rather than copied.
+pub struct Class {
+    pub name: Rc<str>,
+    pub methods: RefCell<HashMap<Rc<str>, Rc<Closure>>>,
+}
+
+impl Class {
+    pub fn new(name: Rc<str>) -> Self {
+        Self {
+            name,
+            methods: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: RefCell<HashMap<Rc<str>, Value>>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+/// A method looked up on an instance, remembering the instance as `this`.
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: Rc<Closure>,
+}
+
+impl Trace for RefCell<Upvalue> {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        // Open upvalues point at the stack, which is a root.
+        if let Ok(upvalue) = self.try_borrow() {
+            if let Upvalue::Closed(value) = &*upvalue {
+                value.trace(visit);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut upvalue) = self.try_borrow_mut() {
+            if let Upvalue::Closed(value) = &mut *upvalue {
+                *value = Value::Nil;
+            }
+        }
+    }
+}
+
+impl Trace for Closure {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        for upvalue in &self.upvalues {
+            visit(gc::address(upvalue));
+        }
+    }
+
+    /// A closure's upvalues are fixed when it is created, so the cycles it is
+    /// part of are broken by clearing the upvalues instead.
+    fn clear(&self) {}
+}
+
+impl Trace for Class {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        if let Ok(methods) = self.methods.try_borrow() {
+            for method in methods.values() {
+                visit(gc::address(method));
+            }
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut methods) = self.methods.try_borrow_mut() {
+            methods.clear();
+        }
+    }
+}
+
+impl Trace for Instance {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        visit(gc::address(&self.class));
+        if let Ok(fields) = self.fields.try_borrow() {
+            for value in fields.values() {
+                value.trace(visit);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut fields) = self.fields.try_borrow_mut() {
+            fields.clear();
+        }
+    }
+}
+
+impl Trace for BoundMethod {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        self.receiver.trace(visit);
+        visit(gc::address(&self.method));
+    }
+
+    fn clear(&self) {}
+}
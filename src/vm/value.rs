@@ -0,0 +1,118 @@
+use std::rc::Rc;
+
+use crate::gc::{self, Address};
+
+use super::{
+    native::NativeFunction,
+    object::{BoundMethod, Class, Closure, Function, Instance},
+};
+
+/// A value on the virtual machine's stack.
+#[derive(Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(Rc<str>),
+    /// A compiled function before it has been wrapped in a closure. These only
+    /// live in constant pools.
+    Function(Rc<Function>),
+    Closure(Rc<Closure>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<Class>),
+    Instance(Rc<Instance>),
+    BoundMethod(Rc<BoundMethod>),
+}
+
+impl Value {
+    pub fn is_falsey(&self) -> bool {
+        return matches!(self, Value::Nil | Value::Bool(false));
+    }
+
+    /// The name of the value's type as it appears in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Function(_) | Value::Closure(_) | Value::Native(_) | Value::BoundMethod(_) => {
+                "a function"
+            }
+            Value::Class(_) => "a class",
+            Value::Instance(_) => "an instance",
+        }
+    }
+
+    /// Visits the object this value refers to, if the collector tracks it.
+    /// Functions only live in constant pools and hold nothing but constants.
+    pub fn trace(&self, visit: &mut dyn FnMut(Address)) {
+        match self {
+            Value::Closure(closure) => visit(gc::address(closure)),
+            Value::Class(class) => visit(gc::address(class)),
+            Value::Instance(instance) => visit(gc::address(instance)),
+            Value::BoundMethod(bound) => visit(gc::address(bound)),
+            Value::Nil
+            | Value::Bool(_)
+            | Value::Number(_)
+            | Value::String(_)
+            | Value::Function(_)
+            | Value::Native(_) => {}
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            (Value::Native(a), Value::Native(b)) => Rc::ptr_eq(a, b),
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::BoundMethod(a), Value::BoundMethod(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Shows values the same way the tree-walking backend shows its
+/// `LiteralValue`s, so that both backends produce identical output.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(bool) => write!(f, "{}", bool),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::String(string) => write!(f, "{}", string),
+            Value::Function(function) => write!(f, "{:?}", function),
+            Value::Closure(closure) => write!(f, "{:?}", closure.function),
+            Value::Native(native) => write!(f, "{:?}", native),
+            Value::Class(class) => write!(f, "{}", class.name),
+            Value::Instance(instance) => write!(f, "{} instance", instance.class.name),
+            Value::BoundMethod(bound) => write!(f, "{:?}", bound.method.function),
+        }
+    }
+}
+
+/// Tags each value with its kind, for disassembly.
+impl std::fmt::Debug for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Nil => write!(f, "None"),
+            Value::Bool(bool) => write!(f, "Bool({:?})", bool),
+            Value::Number(number) => write!(f, "Number({:?})", number),
+            Value::String(string) => write!(f, "String({:?})", string),
+            Value::Function(function) => write!(f, "Function({:?})", function),
+            Value::Closure(closure) => write!(f, "Function({:?})", closure.function),
+            Value::Native(native) => write!(f, "Native({:?})", native),
+            Value::Class(class) => write!(f, "Class({})", class.name),
+            Value::Instance(instance) => write!(f, "Instance({} instance)", instance.class.name),
+            Value::BoundMethod(bound) => write!(f, "Function({:?})", bound.method.function),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use common::run_on_each_backend;
+
+/// Runs `source` on both backends and checks that they behave identically.
+fn assert_backends_agree(source: &str) {
+    let [(_, tree), (_, vm)]: [_; 2] = run_on_each_backend(&["--color=never"], source)
+        .try_into()
+        .expect("There are two backends");
+    assert_eq!(
+        String::from_utf8_lossy(&tree.stdout),
+        String::from_utf8_lossy(&vm.stdout),
+        "stdout differs for:\n{}",
+        source
+    );
+    assert_eq!(
+        without_paths(&tree.stderr),
+        without_paths(&vm.stderr),
+        "stderr differs for:\n{}",
+        source
+    );
+    assert_eq!(tree.status.code(), vm.status.code());
+}
+
+/// Drops the temporary script's name, which differs between the two runs.
+fn without_paths(stderr: &[u8]) -> String {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .map(|line| match line.split_once(".lox:") {
+            Some((_, location)) => location,
+            None => line,
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+#[test]
+fn closures_capture_variables_not_values() {
+    assert_backends_agree(
+        r#"
+        fun makeCounter() {
+            var count = 0;
+            fun increment() {
+                count = count + 1;
+                return count;
+            }
+            return increment;
+        }
+        var first = makeCounter();
+        var second = makeCounter();
+        print first();
+        print first();
+        print second();
+
+        {
+            var shared = "before";
+            fun read() { return shared; }
+            shared = "after";
+            print read();
+        }
+
+        for (var i = 0; i < 3; i = i + 1) {
+            var j = i;
+            fun get() { return j; }
+            print get();
+        }
+        "#,
+    );
+}
+
+#[test]
+fn classes_inheritance_and_super() {
+    assert_backends_agree(
+        r#"
+        class Animal {
+            init(name) { this.name = name; }
+            speak() { return this.name + " makes a sound"; }
+        }
+        class Dog < Animal {
+            init(name) { super.init(name); this.tricks = 0; }
+            speak() { return super.speak() + " (woof)"; }
+        }
+        var dog = Dog("Rex");
+        print dog.speak();
+        print dog.tricks;
+        print dog;
+        print Dog;
+        print dog.speak;
+        print dog.init("Max");
+        print dog.name;
+        "#,
+    );
+}
+
+#[test]
+fn arithmetic_comparison_and_control_flow() {
+    assert_backends_agree(
+        r#"
+        fun fib(n) {
+            if (n < 2) return n;
+            return fib(n - 1) + fib(n - 2);
+        }
+        print fib(15);
+        print 7 / 2;
+        print 1 <= 1;
+        print 2 >= 3;
+        print "a" + "b" == "ab";
+        print nil != false;
+        print !nil;
+        print -(3 * 4);
+        var i = 0;
+        while (i < 3) {
+            if (i == 1) print "one"; else print i;
+            i = i + 1;
+        }
+        "#,
+    );
+}
+
+#[test]
+fn runtime_errors_match() {
+    let programs = [
+        "print 1 - \"a\";",
+        "print -\"x\";",
+        "print \"a\" + 1;",
+        "print missing;",
+        "missing = 1;",
+        "var x = 1;\nx();",
+        "fun f(a) {}\nf(1, 2);",
+        "class A {}\nA(1);",
+        "class A {}\nprint A().nope;",
+        "print 3.x;",
+        "var n = 1;\nn.x = 2;",
+        "var NotClass = 1;\nclass B < NotClass {}",
+        "class A < A {}",
+        "class A {}\nclass B < A { m() { return super.nope; } }\nB().m();",
+        "print \"before\";\nprint 1 < nil;",
+    ];
+    for program in programs {
+        assert_backends_agree(program);
+    }
+}
+
+#[test]
+fn call_depth_limit_matches() {
+    let programs = [
+        "fun f(n) { if (n == 0) return 0; return 1 + f(n - 1); }\nprint f(9999);",
+        "fun f(n) { if (n == 0) return 0; return 1 + f(n - 1); }\nprint f(10000);",
+        "fun f() { f(); }\nprint \"before\";\nf();",
+        "class A { init() { A(); } }\nA();",
+        "class A { m() { return this.m(); } }\nA().m();",
+    ];
+    for program in programs {
+        assert_backends_agree(program);
+    }
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::run_lox_with;
+
+#[test]
+fn dump_bytecode_prints_chunks_without_running() {
+    let source = "var a = 1;\nfun add(b) {\n  return a + b;\n}\nprint add(2);\n";
+    let output = run_lox_with(&["--dump-bytecode"], source);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!stdout.lines().any(|line| line == "3"), "{}", stdout);
+    assert!(stdout.starts_with("== <script> ==\n"), "{}", stdout);
+    assert!(
+        stdout.contains("0000    1 Constant             0 Number(1.0)\n"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("    | DefineGlobal         1 String(\"a\")\n"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("    5 GetGlobal "), "{}", stdout);
+    assert!(
+        stdout.contains("    | Call                 1\n"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("\n== <fn add> ==\n"), "{}", stdout);
+    assert!(stdout.contains("0000    3 GetGlobal "), "{}", stdout);
+    assert!(
+        stdout.contains("    | GetLocal             1\n"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn dump_bytecode_shows_jump_targets_and_captures() {
+    let source = "{\n  var n = 0;\n  fun get() { return n; }\n  while (n < 2) n = n + 1;\n}\n";
+    let stdout = String::from_utf8(run_lox_with(&["--dump-bytecode"], source).stdout).unwrap();
+
+    assert!(stdout.contains("Closure "), "{}", stdout);
+    assert!(
+        stdout.contains("|                     local 1\n"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("JumpIfFalse "), "{}", stdout);
+    let loop_line = stdout.lines().find(|line| line.contains("Loop ")).unwrap();
+    assert!(loop_line.contains(" -> "), "{}", loop_line);
+    assert!(stdout.contains("GetUpvalue           0\n"), "{}", stdout);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{stderr_of, stdout_on_each_backend};
+
+#[test]
+fn instances_hold_fields_and_call_methods() {
+    let source = r#"
+        class Point {
+            sum() { return this.x + this.y; }
+        }
+        var point = Point();
+        point.x = 1;
+        point.y = 2;
+        print point.sum();
+        point.x = 10;
+        print point.sum();
+        print point;
+        print Point;
+    "#;
+    assert_eq!(
+        stdout_on_each_backend(source),
+        "3\n12\nPoint instance\nPoint\n"
+    );
+}
+
+#[test]
+fn fields_shadow_methods() {
+    let source = r#"
+        class Box {
+            name() { return "method"; }
+        }
+        var box = Box();
+        print box.name();
+        box.name = "field";
+        print box.name;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "method\nfield\n");
+}
+
+#[test]
+fn bound_methods_remember_this() {
+    let source = r#"
+        class Person {
+            init(name) { this.name = name; }
+            greet() { return "I am " + this.name; }
+        }
+        var greet = Person("Ada").greet;
+        var other = Person("Grace");
+        other.greet = greet;
+        print greet();
+        print other.greet();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "I am Ada\nI am Ada\n");
+}
+
+#[test]
+fn this_in_a_nested_function_is_the_instance() {
+    let source = r#"
+        class Counter {
+            init() { this.count = 0; }
+            incrementer() {
+                fun increment() { this.count = this.count + 1; }
+                return increment;
+            }
+        }
+        var counter = Counter();
+        var increment = counter.incrementer();
+        increment();
+        increment();
+        print counter.count;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "2\n");
+}
+
+#[test]
+fn init_runs_on_construction_and_returns_the_instance() {
+    let source = r#"
+        class Pair {
+            init(first, second) {
+                this.first = first;
+                this.second = second;
+                print "init";
+                return;
+            }
+        }
+        var pair = Pair(1, 2);
+        print pair.first + pair.second;
+        print pair.init(3, 4) == pair;
+        print pair.first;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "init\n3\ninit\ntrue\n3\n");
+}
+
+#[test]
+fn classes_check_the_arity_of_init() {
+    let source = "class Pair { init(a, b) {} }\nPair(1);\n";
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(
+        stderr.contains("error[E0206]: Expected 2 arguments but got 1."),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains(".lox:2:7\n"), "{}", stderr);
+}
+
+#[test]
+fn undefined_properties_are_runtime_errors() {
+    let stderr = stderr_of(&["--color=never"], "class A {}\nprint A().missing;\n");
+    assert!(
+        stderr.contains("error[E0207]: Undefined property 'missing'."),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains(".lox:2:11\n"), "{}", stderr);
+}
+
+#[test]
+fn only_instances_have_properties_and_fields() {
+    let stderr = stderr_of(&["--color=never"], "print \"text\".length;\n");
+    assert!(
+        stderr.contains("error[E0208]: Only instances have properties."),
+        "{}",
+        stderr
+    );
+
+    let stderr = stderr_of(&["--color=never"], "var n = 1;\nn.field = 2;\n");
+    assert!(
+        stderr.contains("error[E0209]: Only instances have fields."),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains(".lox:2:3\n"), "{}", stderr);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::stdout_of;
+
+#[test]
+fn counter_keeps_its_own_state() {
+    let source = r#"
+        fun makeCounter() {
+            var count = 0;
+            fun increment() {
+                count = count + 1;
+                return count;
+            }
+            return increment;
+        }
+
+        var first = makeCounter();
+        var second = makeCounter();
+        print first();
+        print first();
+        print second();
+        print first();
+    "#;
+    assert_eq!(stdout_of(source), "1\n2\n1\n3\n");
+}
+
+#[test]
+fn two_closures_share_captured_state() {
+    let source = r#"
+        var get;
+        var set;
+        fun makePair() {
+            var value = "initial";
+            fun getter() { return value; }
+            fun setter(v) { value = v; }
+            get = getter;
+            set = setter;
+        }
+
+        makePair();
+        print get();
+        set("updated");
+        print get();
+    "#;
+    assert_eq!(stdout_of(source), "initial\nupdated\n");
+}
+
+#[test]
+fn closure_outlives_its_defining_block() {
+    let source = r#"
+        var saved;
+        {
+            var local = "from block";
+            fun show() { print local; }
+            saved = show;
+        }
+        var local = "global";
+        saved();
+    "#;
+    assert_eq!(stdout_of(source), "from block\n");
+}
+
+#[test]
+fn closure_uses_defining_scope_not_call_site() {
+    let source = r#"
+        var x = "global";
+        fun outer() {
+            var x = "outer";
+            fun inner() { return x; }
+            return inner;
+        }
+        fun caller(f) {
+            var x = "caller";
+            return f();
+        }
+        print caller(outer());
+    "#;
+    assert_eq!(stdout_of(source), "outer\n");
+}
+
+#[test]
+fn nested_functions_return_through_loops() {
+    let source = r#"
+        fun find(limit) {
+            fun step(n) {
+                while (true) {
+                    if (n >= limit) return n;
+                    n = n + 1;
+                }
+            }
+            return step(0) * 2;
+        }
+        print find(5);
+    "#;
+    assert_eq!(stdout_of(source), "10\n");
+}
@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The `--backend` flag of each backend.
+pub const BACKENDS: [&str; 2] = ["--backend=tree", "--backend=vm"];
+
+static SCRIPT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn script_path() -> PathBuf {
+    let id = SCRIPT_COUNT.fetch_add(1, Ordering::SeqCst);
+    let name = format!("rloxi-{}-{}.lox", std::process::id(), id);
+    return std::env::temp_dir().join(name);
+}
+
+/// Runs the `rloxi` binary with `args` and no script.
+pub fn run_rloxi(args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_rloxi"))
+        .args(args)
+        .output()
+        .expect("Failed to run rloxi");
+}
+
+/// Writes `source` to a temporary script and runs the `rloxi` binary on it.
+pub fn run_lox(source: &str) -> Output {
+    return run_lox_with(&[], source);
+}
+
+/// Like `run_lox`, passing `args` before the script path.
+pub fn run_lox_with(args: &[&str], source: &str) -> Output {
+    let path = script_path();
+    fs::write(&path, source).expect("Failed to write script");
+    let output = Command::new(env!("CARGO_BIN_EXE_rloxi"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("Failed to run rloxi");
+    fs::remove_file(&path).ok();
+    return output;
+}
+
+/// Runs `source` on each backend, passing `args` after the backend flag.
+/// Returns the flag of each backend along with what it did.
+pub fn run_on_each_backend(args: &[&str], source: &str) -> Vec<(&'static str, Output)> {
+    return BACKENDS
+        .iter()
+        .map(|backend| {
+            let args: Vec<&str> = [*backend].iter().chain(args).copied().collect();
+            return (*backend, run_lox_with(&args, source));
+        })
+        .collect();
+}
+
+/// Runs `source` on each backend, checks that they print the same thing and
+/// exit the same way, and returns what they printed.
+pub fn stdout_on_each_backend(source: &str) -> String {
+    let mut runs = run_on_each_backend(&[], source).into_iter();
+    let (_, first) = runs.next().expect("There is at least one backend");
+    let stdout = String::from_utf8(first.stdout).expect("stdout is not UTF-8");
+    for (backend, output) in runs {
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            stdout,
+            "{} prints something else for:\n{}",
+            backend,
+            source
+        );
+        assert_eq!(output.status.code(), first.status.code(), "{}", backend);
+    }
+    return stdout;
+}
+
+pub fn stdout_of(source: &str) -> String {
+    let output = run_lox(source);
+    return String::from_utf8(output.stdout).expect("stdout is not UTF-8");
+}
+
+pub fn stderr_of(args: &[&str], source: &str) -> String {
+    let output = run_lox_with(args, source);
+    return String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+}
+
+/// The code, line and column of each diagnostic in `--error-format=json`
+/// output, as `E0000 line:column`.
+pub fn diagnostic_locations(stderr: &str) -> Vec<String> {
+    return stderr
+        .lines()
+        .map(|line| {
+            let field = |name: &str| {
+                let start = line.find(name).expect("Missing field") + name.len();
+                let end = start + line[start..].find([',', '"']).expect("Unterminated field");
+                return line[start..end].to_string();
+            };
+            let code = field(r#""code":""#);
+            return format!("{} {}:{}", code, field(r#""line":"#), field(r#""column":"#));
+        })
+        .collect();
+}
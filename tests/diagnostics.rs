@@ -0,0 +1,37 @@
+mod common;
+
+use common::stderr_of;
+
+#[test]
+fn json_format_emits_one_object_per_diagnostic() {
+    let source = "var a = 1 +;\nprint @;\n";
+    let stderr = stderr_of(&["--error-format=json"], source);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", stderr);
+    assert!(lines[0]
+        .starts_with(r#"{"code":"E0002","severity":"error","message":"Unexpected character.","#));
+    assert!(lines[0].contains(r#""line":2,"column":7,"span":{"start":19,"end":20,"#));
+    assert!(lines[1].starts_with(r#"{"code":"E0103","#));
+    assert!(lines[1].contains(r#""line":1,"column":12,"#));
+}
+
+#[test]
+fn json_format_includes_operand_labels() {
+    let source = "print 1 - \"two\";\n";
+    let stderr = stderr_of(&["--error-format=json"], source);
+    assert!(stderr.starts_with(r#"{"code":"E0202","#), "{}", stderr);
+    assert!(stderr.contains(
+        r#""labels":[{"message":"this is a number","span":{"start":6,"end":7,"line":1,"column":7}},"#
+    ));
+    assert!(stderr.contains(r#"{"message":"this is a string","span":{"start":10,"end":15,"#));
+}
+
+#[test]
+fn json_format_reports_runtime_errors() {
+    let stderr = stderr_of(&["--error-format=json"], "print undefined;\n");
+    assert!(
+        stderr.contains(r#""message":"Undefined variable 'undefined'.""#),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{run_rloxi, stderr_of};
+
+fn codes() -> Vec<String> {
+    // E0008 was never reachable and has been retired.
+    let lexer = (1..=9).filter(|n| *n != 8).map(|n| format!("E{:04}", n));
+    let parser = (101..=127).map(|n| format!("E{:04}", n));
+    let runtime = (201..=213).map(|n| format!("E{:04}", n));
+    let compiler = (301..=304).map(|n| format!("E{:04}", n));
+    lexer.chain(parser).chain(runtime).chain(compiler).collect()
+}
+
+#[test]
+fn every_code_has_an_explanation() {
+    for code in codes() {
+        let output = run_rloxi(&["--explain", &code]);
+        assert!(output.status.success(), "no explanation for {}", code);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with(&format!("{}: ", code)), "{}", stdout);
+        assert!(stdout.contains("Erroneous code example:"), "{}", stdout);
+        assert!(stdout.contains("Fixed:"), "{}", stdout);
+    }
+}
+
+#[test]
+fn unknown_code_is_a_usage_error() {
+    let output = run_rloxi(&["--explain", "E9999"]);
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn retired_codes_are_unknown() {
+    let output = run_rloxi(&["--explain", "E0008"]);
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn diagnostics_show_their_code() {
+    let stderr = stderr_of(&["--color=never"], "print -\"x\";\n");
+    assert!(
+        stderr.starts_with("error[E0201]: Operand must be a number.\n"),
+        "{}",
+        stderr
+    );
+}
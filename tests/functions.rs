@@ -0,0 +1,102 @@
+mod common;
+
+use common::{run_lox, run_on_each_backend, stdout_of, stdout_on_each_backend};
+
+#[test]
+fn calls_chain_on_returned_functions() {
+    let source = r#"
+        fun adder(a) {
+            fun add(b) { return a + b; }
+            return add;
+        }
+        print adder(1)(2);
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "3\n");
+}
+
+#[test]
+fn a_bare_return_returns_nil() {
+    let source = r#"
+        fun nothing() {
+            print "before";
+            return;
+            print "after";
+        }
+        print nothing();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "before\nnil\n");
+}
+
+#[test]
+fn return_leaves_loops_and_nested_blocks() {
+    let source = r#"
+        fun find(limit) {
+            for (var i = 0; i < limit; i = i + 1) {
+                while (true) {
+                    { if (i == 3) return i; }
+                    i = i + 1;
+                }
+            }
+            return -1;
+        }
+        print find(10);
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "3\n");
+}
+
+#[test]
+fn calls_check_the_arity_of_functions() {
+    let source = "fun pair(a, b) {}\npair(1);\n";
+    for (backend, output) in run_on_each_backend(&["--color=never"], source) {
+        let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+        let message = "error[E0206]: Expected 2 arguments but got 1.";
+        assert!(stderr.contains(message), "{}: {}", backend, stderr);
+        assert!(stderr.contains(".lox:2:7\n"), "{}: {}", backend, stderr);
+    }
+}
+
+#[test]
+fn deep_recursion_within_the_limit_runs() {
+    let source = r#"
+        fun depth(n) {
+            if (n == 0) return 0;
+            return 1 + depth(n - 1);
+        }
+        print depth(9999);
+    "#;
+    assert_eq!(stdout_of(source), "9999\n");
+}
+
+#[test]
+fn runaway_recursion_reports_a_stack_overflow() {
+    let output = run_lox("fun f() { f(); }\nf();\n");
+    let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr.contains("error[E0213]: Stack overflow."),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains(":1:13"), "{}", stderr);
+}
+
+#[test]
+fn stack_hungry_recursion_reports_a_stack_overflow() {
+    let mut expression = String::from("this.sum(n - 1)");
+    for _ in 0..30 {
+        expression = format!("(1 + {})", expression);
+    }
+    let source = format!(
+        "class A {{ sum(n) {{ if (n == 0) return 0; {{ {{ return {}; }} }} }} }}\n\
+         print A().sum(20000);\n",
+        expression
+    );
+    let output = run_lox(&source);
+    let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr.contains("error[E0213]: Stack overflow."),
+        "{}",
+        stderr
+    );
+}
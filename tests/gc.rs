@@ -0,0 +1,75 @@
+mod common;
+
+use common::{run_on_each_backend, stdout_on_each_backend};
+
+#[test]
+fn gc_frees_closures_that_capture_themselves() {
+    let source = r#"
+        fun makeCycle() {
+            var self;
+            fun f() { return self; }
+            self = f;
+        }
+        for (var i = 0; i < 10; i = i + 1) makeCycle();
+        print gc();
+        print gc();
+    "#;
+    // Each call leaves a closure and the variable it captured behind.
+    assert_eq!(stdout_on_each_backend(source), "20\n0\n");
+}
+
+#[test]
+fn gc_keeps_cycles_reachable_from_globals() {
+    let source = r#"
+        class Node {}
+        var node = Node();
+        node.next = node;
+        node.value = "kept";
+        print gc();
+        print node.next.next.value;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "0\nkept\n");
+}
+
+#[test]
+fn gc_keeps_cycles_reachable_from_active_calls() {
+    let source = r#"
+        class Node {}
+        fun run() {
+            var node = Node();
+            node.next = node;
+            fun get() { return node; }
+            node.get = get;
+            gc();
+            return node.get().next == node;
+        }
+        print run();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "true\n");
+}
+
+#[test]
+fn gc_stats_reports_automatic_collections() {
+    let source = r#"
+        fun makeCycle() {
+            var self;
+            fun f() { return self; }
+            self = f;
+        }
+        for (var i = 0; i < 20000; i = i + 1) makeCycle();
+    "#;
+    for (_, output) in run_on_each_backend(&["--gc-stats"], source) {
+        let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+        let counts: Vec<usize> = stderr
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|number| number.parse().ok())
+            .collect();
+        let [collections, tracked, freed] = counts[..] else {
+            panic!("unexpected stats: {}", stderr);
+        };
+        assert!(stderr.starts_with("gc: "), "{}", stderr);
+        assert!(collections > 0, "{}", stderr);
+        assert!(tracked >= 40000, "{}", stderr);
+        assert!(freed > 30000, "{}", stderr);
+    }
+}
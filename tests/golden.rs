@@ -0,0 +1,36 @@
+//! Runs every script in `tests/golden` on both backends and compares what it
+//! prints with the `.out` file of the same name.
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::run_on_each_backend;
+
+#[test]
+fn scripts_print_their_golden_output() {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut scripts: Vec<PathBuf> = fs::read_dir(&directory)
+        .expect("Failed to read tests/golden")
+        .map(|entry| entry.expect("Failed to read tests/golden").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty());
+
+    for script in scripts {
+        let source = fs::read_to_string(&script).expect("Failed to read script");
+        let expected = fs::read_to_string(script.with_extension("out"))
+            .expect("Every golden script needs a .out file");
+        for (backend, output) in run_on_each_backend(&[], &source) {
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                expected,
+                "{} with {}\n{}",
+                script.display(),
+                backend,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+}
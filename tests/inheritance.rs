@@ -0,0 +1,128 @@
+mod common;
+
+use common::{run_lox_with, run_on_each_backend, stderr_of, stdout_on_each_backend};
+
+#[test]
+fn methods_are_inherited_through_the_chain() {
+    let source = r#"
+        class A { name() { return "A"; } only() { return "only in A"; } }
+        class B < A { name() { return "B"; } }
+        class C < B {}
+        var c = C();
+        print c.name();
+        print c.only();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "B\nonly in A\n");
+}
+
+#[test]
+fn super_calls_the_superclass_method_on_the_same_instance() {
+    let source = r#"
+        class A {
+            describe() { return "A sees " + this.tag; }
+        }
+        class B < A {
+            describe() { return "B then " + super.describe(); }
+        }
+        class C < B {
+            describe() { return "C then " + super.describe(); }
+        }
+        var c = C();
+        c.tag = "c";
+        print c.describe();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "C then B then A sees c\n");
+}
+
+#[test]
+fn super_binds_to_the_class_it_appears_in() {
+    let source = r#"
+        class A { method() { return "A"; } }
+        class B < A {
+            method() { return "B"; }
+            test() { return super.method(); }
+        }
+        class C < B {}
+        print C().test();
+        var bound = C().test;
+        print bound();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "A\nA\n");
+}
+
+#[test]
+fn initializers_are_inherited_and_reachable_through_super() {
+    let source = r#"
+        class Base { init(value) { this.value = value; } }
+        class Derived < Base {
+            init(value) {
+                super.init(value * 2);
+                this.extra = true;
+            }
+        }
+        class Plain < Base {}
+        var derived = Derived(2);
+        print derived.value;
+        print derived.extra;
+        print Plain(5).value;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "4\ntrue\n5\n");
+}
+
+#[test]
+fn superclasses_must_be_classes() {
+    let source = "var NotAClass = \"x\";\nclass B < NotAClass {}\n";
+    for (backend, output) in run_on_each_backend(&["--color=never"], source) {
+        let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+        let message = "error[E0210]: Superclass must be a class.";
+        assert!(stderr.contains(message), "{}: {}", backend, stderr);
+        assert!(stderr.contains(".lox:2:11\n"), "{}: {}", backend, stderr);
+        assert!(
+            stderr.contains("  |           ^^^^^^^^^\n"),
+            "{}: {}",
+            backend,
+            stderr
+        );
+    }
+}
+
+#[test]
+fn classes_cannot_inherit_from_themselves() {
+    let source = "class A < A {}\n";
+    for (backend, output) in run_on_each_backend(&["--color=never"], source) {
+        let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+        let message = "error[E0211]: A class can't inherit from itself.";
+        assert!(stderr.contains(message), "{}: {}", backend, stderr);
+        assert!(stderr.contains(".lox:1:11\n"), "{}: {}", backend, stderr);
+    }
+}
+
+#[test]
+fn misplaced_super_is_rejected_before_the_program_runs() {
+    // The resolver reports these, so the runtime's E0212 is never reached
+    // by a program run from a file.
+    let stderr = stderr_of(
+        &["--color=never"],
+        "print \"ran\";\nfun f() { super.m(); }\n",
+    );
+    let message = "error[E0125]: Can't use 'super' outside of a class.";
+    assert!(stderr.contains(message), "{}", stderr);
+    assert!(!stderr.contains("E0212"), "{}", stderr);
+
+    let source = "print \"ran\";\nclass A { m() { return super.m(); } }\n";
+    let output = run_lox_with(&["--color=never"], source);
+    let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+    let message = "error[E0126]: Can't use 'super' in a class with no superclass.";
+    assert!(stderr.contains(message), "{}", stderr);
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn missing_superclass_methods_are_undefined_properties() {
+    let source = "class A {}\nclass B < A { m() { return super.nope(); } }\nB().m();\n";
+    for (backend, output) in run_on_each_backend(&["--color=never"], source) {
+        let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+        let message = "error[E0207]: Undefined property 'nope'.";
+        assert!(stderr.contains(message), "{}: {}", backend, stderr);
+    }
+}
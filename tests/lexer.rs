@@ -0,0 +1,175 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{run_lox, stderr_of, stdout_of};
+
+#[test]
+fn strings_and_comments_may_contain_any_characters() {
+    let source = "print \"héllo wörld ✓\"; // ça va? 日本語\nprint \"日本\" + \"語\";\n";
+    assert_eq!(stdout_of(source), "héllo wörld ✓\n日本語\n");
+}
+
+#[test]
+fn columns_count_characters_and_spans_count_bytes() {
+    let source = "var s = \"ünï\"; @\n";
+    let stderr = stderr_of(&["--error-format=json"], source);
+    assert!(
+        stderr.contains(r#""line":1,"column":16,"span":{"start":17,"end":18,"#),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn unexpected_multibyte_characters_are_reported_whole() {
+    let stderr = stderr_of(&["--color=never"], "print 1; §\n");
+    assert!(
+        stderr.contains("error[E0002]: Unexpected character."),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("1 | print 1; §\n  |          ^\n"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn lexing_a_megabyte_takes_linear_time() {
+    let mut source = "// ünïcödé comment ✓ 日本語 with some padding after it\n".repeat(20_000);
+    source.push_str(&format!("var text = \"{}\";\n", "é".repeat(100_000)));
+    source.push_str("print \"done\";\n");
+    assert!(source.len() > 1_000_000);
+
+    let start = Instant::now();
+    let output = run_lox(&source);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+    // Scanning character by character from the start of the source takes
+    // minutes on input this size.
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn identifiers_may_be_greek() {
+    let source = "fun Σ(α, β) { return α + β; }\nvar π = 3;\nprint Σ(π, 1);\n";
+    assert_eq!(stdout_of(source), "4\n");
+}
+
+#[test]
+fn identifiers_may_be_cjk() {
+    let source = "var 変数 = \"値\";\nclass 猫 { 鳴く() { return 変数; } }\nprint 猫().鳴く();\n";
+    assert_eq!(stdout_of(source), "値\n");
+}
+
+#[test]
+fn accented_identifiers_match_in_any_normalization_form() {
+    // The declaration spells é as one code point, the use as e and a
+    // combining acute accent.
+    let source = "var caf\u{e9} = \"crème\";\nprint cafe\u{301};\n";
+    assert_eq!(stdout_of(source), "crème\n");
+}
+
+#[test]
+fn symbols_are_not_identifiers() {
+    let stderr = stderr_of(&["--color=never"], "var ✓ = 1;\n");
+    assert!(
+        stderr.contains("error[E0002]: Unexpected character."),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn numbers_may_use_radix_prefixes_exponents_and_separators() {
+    let source = "print 0xFF;\nprint 0b1010;\nprint 0o755;\nprint 1e-9;\nprint 6.02E23;\n\
+                  print 1_000_000;\n";
+    assert_eq!(
+        stdout_of(source),
+        "255\n10\n493\n0.000000001\n602000000000000000000000\n1000000\n"
+    );
+}
+
+#[test]
+fn radix_prefixes_need_digits() {
+    let stderr = stderr_of(&["--color=never"], "print 0x;\n");
+    assert!(
+        stderr.contains("error[E0004]: Expect digits after '0x'."),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("E0103"), "{}", stderr);
+}
+
+#[test]
+fn exponents_need_digits() {
+    let stderr = stderr_of(&["--color=never"], "print 1e;\nprint 2E+;\n");
+    assert!(
+        stderr.contains("error[E0005]: Expect exponent digits after '1e'."),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("error[E0005]: Expect exponent digits after '2E+'."),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn digits_outside_the_radix_are_reported() {
+    let stderr = stderr_of(&["--color=never"], "print 0b102;\n");
+    assert!(
+        stderr.contains("error[E0006]: Invalid digit '2' in binary literal."),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("1 | print 0b102;\n  |           ^\n"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn separators_must_sit_between_digits() {
+    for source in [
+        "print 1_;\n",
+        "print 1__0;\n",
+        "print 0x_F;\n",
+        "print 1e_5;\n",
+    ] {
+        let stderr = stderr_of(&["--color=never"], source);
+        assert!(stderr.contains("error[E0007]"), "{}: {}", source, stderr);
+    }
+}
+
+#[test]
+fn block_comments_nest() {
+    let source = "print 1; /* outer /* inner */ print 2; */ print 3;\n";
+    assert_eq!(stdout_of(source), "1\n3\n");
+}
+
+#[test]
+fn block_comments_keep_line_numbers() {
+    let source = "/* one\n   two\n   /* three\n */ four */\nprint nothing;\n";
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(
+        stderr.contains("5 | print nothing;\n  |       ^^^^^^^\n"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn unterminated_block_comments_point_at_their_opening() {
+    let source = "print 1;\n/* open\n/* closed */\nprint 2;\n";
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(
+        stderr.contains("error[E0009]: Unterminated block comment."),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("2 | /* open\n  | ^^\n"), "{}", stderr);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::stdout_on_each_backend;
+
+#[test]
+fn and_returns_the_first_falsey_operand_or_the_last() {
+    let source = r#"
+        print 1 and 2;
+        print nil and 2;
+        print false and nil;
+        print true and "last";
+        print 0 and "";
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "2\nnil\nfalse\nlast\n\n");
+}
+
+#[test]
+fn or_returns_the_first_truthy_operand_or_the_last() {
+    let source = r#"
+        print 1 or 2;
+        print nil or 2;
+        print false or nil;
+        print nil or false;
+        print "" or "unused";
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "1\n2\nnil\nfalse\n\n");
+}
+
+#[test]
+fn and_skips_its_right_operand_when_the_left_is_falsey() {
+    let source = r#"
+        var touched = "no";
+        false and (touched = "and");
+        nil and (touched = "and");
+        print touched;
+        true and (touched = "yes");
+        print touched;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "no\nyes\n");
+}
+
+#[test]
+fn or_skips_its_right_operand_when_the_left_is_truthy() {
+    let source = r#"
+        var touched = "no";
+        true or (touched = "or");
+        0 or (touched = "or");
+        print touched;
+        false or (touched = "yes");
+        print touched;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "no\nyes\n");
+}
+
+#[test]
+fn skipped_operands_are_never_called() {
+    let source = r#"
+        var calls = 0;
+        fun count(result) {
+            calls = calls + 1;
+            return result;
+        }
+        count(false) and count(true) and count(true);
+        print calls;
+        count(true) or count(false) or count(false);
+        print calls;
+        count(true) and count(false) or count(true);
+        print calls;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "1\n2\n5\n");
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    let source = r#"
+        print true or false and false;
+        print (true or false) and false;
+        print false and true or "fallback";
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "true\nfalse\nfallback\n");
+}
+
+#[test]
+fn logical_operators_drive_control_flow() {
+    let source = r#"
+        var i = 0;
+        var steps = 0;
+        while (i < 10 and steps < 3) {
+            i = i + 1;
+            steps = steps + 1;
+        }
+        print i;
+        if (nil or i == 3) print "reached";
+        for (var j = 0; j < 5 or false; j = j + 1) steps = steps + 1;
+        print steps;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "3\nreached\n8\n");
+}
+
+#[test]
+fn errors_in_skipped_operands_are_not_raised() {
+    let source = r#"
+        print false and undefined;
+        print true or -"not a number";
+        print nil and nil();
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "false\ntrue\nnil\n");
+}
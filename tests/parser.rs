@@ -0,0 +1,57 @@
+mod common;
+
+use common::{diagnostic_locations, run_lox_with};
+
+/// Runs `source` and returns the location of each diagnostic, checking that
+/// the program never started.
+fn errors_of(source: &str) -> Vec<String> {
+    let output = run_lox_with(&["--error-format=json"], source);
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    diagnostic_locations(&String::from_utf8(output.stderr).expect("stderr is not UTF-8"))
+}
+
+#[test]
+fn every_statement_with_a_mistake_is_reported() {
+    let source = "var a = ;\nprint a\nvar b = 2;\nclass { }\nprint (1 + 2;\nvar = 3;\n";
+    assert_eq!(
+        errors_of(&format!("{}print \"ran\";\n", source)),
+        [
+            "E0103 1:9",
+            "E0104 3:1",
+            "E0113 4:7",
+            "E0102 5:13",
+            "E0106 6:5"
+        ]
+    );
+}
+
+#[test]
+fn parsing_resumes_inside_blocks_and_bodies() {
+    let source = r#"fun f() {
+  var = 1;
+  print 2 * ;
+}
+class A {
+  m() { return 1 + ; }
+}
+{
+  print ) ;
+}
+"#;
+    assert_eq!(
+        errors_of(source),
+        ["E0106 2:7", "E0103 3:13", "E0103 6:20", "E0103 9:9"]
+    );
+}
+
+#[test]
+fn lexer_and_parser_errors_are_reported_together() {
+    let errors = errors_of("var a = @;\nvar b = 1 +;\nprint #;\n");
+    assert!(errors.contains(&"E0002 1:9".to_string()));
+    assert!(errors.contains(&"E0002 3:7".to_string()));
+    assert!(errors.contains(&"E0103 2:12".to_string()));
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{run_lox_with, stderr_of};
+
+/// The human-readable diagnostics for `source`, with the temporary script's
+/// path replaced by `script.lox`.
+fn rendered(args: &[&str], source: &str) -> String {
+    stderr_of(args, source)
+        .lines()
+        .map(|line| match line.split_once(".lox:") {
+            Some((_, location)) if line.starts_with(" --> ") => {
+                format!(" --> script.lox:{}\n", location)
+            }
+            _ => format!("{}\n", line),
+        })
+        .collect()
+}
+
+#[test]
+fn a_caret_marks_a_single_character() {
+    let expected = "\
+error[E0208]: Only instances have properties.
+ --> script.lox:2:9
+  |
+2 | print s.x;
+  |         ^
+
+";
+    assert_eq!(
+        rendered(&["--color=never"], "var s = \"ab\";\nprint s.x;\n"),
+        expected
+    );
+}
+
+#[test]
+fn carets_underline_the_whole_token() {
+    let expected = "\
+error[E0204]: Undefined variable 'undefinedName'.
+ --> script.lox:1:7
+  |
+1 | print undefinedName;
+  |       ^^^^^^^^^^^^^
+
+";
+    assert_eq!(
+        rendered(&["--color=never"], "print undefinedName;\n"),
+        expected
+    );
+}
+
+#[test]
+fn operands_are_underlined_and_labeled() {
+    let expected = "\
+error[E0202]: Operands must be numbers.
+ --> script.lox:2:9
+  |
+2 | print x - \"two\";
+  |       - ^ ----- this is a string
+  |       |
+  |       this is a number
+
+";
+    assert_eq!(
+        rendered(&["--color=never"], "var x = 1;\nprint x - \"two\";\n"),
+        expected
+    );
+}
+
+#[test]
+fn labels_on_other_lines_get_their_own_snippet() {
+    let expected = "\
+error[E0203]: Operands must be two numbers or include a string
+ --> script.lox:3:10
+  |
+3 | print (x +
+  |        - ^
+  |        |
+  |        this is a number
+4 |   nil) * 2;
+  |   --- this is nil
+
+";
+    let source = "var x = 1;\n\nprint (x +\n  nil) * 2;\n";
+    assert_eq!(rendered(&["--color=never"], source), expected);
+}
+
+#[test]
+fn every_diagnostic_is_rendered_in_order() {
+    let stderr = rendered(&["--color=never"], "var a = @;\nprint #;\n");
+    let headers: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("error"))
+        .collect();
+    let unexpected = "error[E0002]: Unexpected character.";
+    let missing = "error[E0103]: Expect expression.";
+    assert_eq!(headers, [unexpected, unexpected, missing, missing]);
+    assert!(
+        stderr.contains("1 | var a = @;\n  |         ^\n"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("2 | print #;\n  |       ^\n"), "{}", stderr);
+}
+
+#[test]
+fn color_always_highlights_the_header_and_carets() {
+    let stderr = rendered(&["--color=always"], "print undefinedName;\n");
+    assert!(
+        stderr.starts_with("\x1b[1;31merror[E0204]\x1b[0m: "),
+        "{:?}",
+        stderr
+    );
+    assert!(
+        stderr.contains("\x1b[1;31m^^^^^^^^^^^^^\x1b[0m"),
+        "{:?}",
+        stderr
+    );
+}
+
+#[test]
+fn color_is_off_when_never_or_not_a_terminal() {
+    for args in [&["--color=never"][..], &["--color=auto"], &[]] {
+        let stderr = rendered(args, "print undefinedName;\n");
+        assert!(!stderr.contains('\x1b'), "{:?}: {:?}", args, stderr);
+    }
+}
+
+#[test]
+fn unknown_color_choices_are_usage_errors() {
+    let output = run_lox_with(&["--color=sometimes"], "print 1;\n");
+    let stderr = String::from_utf8(output.stderr).expect("stderr is not UTF-8");
+    assert_eq!(output.status.code(), Some(64));
+    assert!(
+        stderr.starts_with("Invalid value for '--color': 'sometimes'."),
+        "{}",
+        stderr
+    );
+    assert!(output.stdout.is_empty());
+}
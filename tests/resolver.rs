@@ -0,0 +1,88 @@
+mod common;
+
+use common::{diagnostic_locations, run_lox_with, stdout_on_each_backend};
+
+/// Runs `source` and returns the location of each diagnostic, checking that
+/// the program never started.
+fn resolve_errors(source: &str) -> Vec<String> {
+    let output = run_lox_with(&["--error-format=json"], source);
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert_eq!(output.status.code(), Some(1));
+    diagnostic_locations(&String::from_utf8(output.stderr).expect("stderr is not UTF-8"))
+}
+
+#[test]
+fn closures_keep_the_binding_they_were_resolved_to() {
+    let source = r#"
+        var a = "global";
+        {
+            fun show() { print a; }
+            show();
+            var a = "block";
+            show();
+            print a;
+        }
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "global\nglobal\nblock\n");
+}
+
+#[test]
+fn locals_cannot_be_read_in_their_own_initializer() {
+    let source = "var a = 1;\n{\n  var a = a + 1;\n}\n";
+    assert_eq!(resolve_errors(source), ["E0120 3:11"]);
+}
+
+#[test]
+fn globals_may_be_read_in_their_own_initializer() {
+    assert_eq!(
+        stdout_on_each_backend("var a = 1;\nvar a = a + 1;\nprint a;\n"),
+        "2\n"
+    );
+}
+
+#[test]
+fn locals_cannot_be_redeclared_in_the_same_scope() {
+    let source = "fun f(a) {\n  var b;\n  var b;\n  var a;\n}\n";
+    assert_eq!(resolve_errors(source), ["E0121 3:7", "E0121 4:7"]);
+}
+
+#[test]
+fn top_level_code_cannot_return() {
+    assert_eq!(resolve_errors("print 1;\nreturn 2;\n"), ["E0122 2:1"]);
+}
+
+#[test]
+fn initializers_cannot_return_a_value() {
+    let source = "class A {\n  init() {\n    if (true) return;\n    return 1;\n  }\n}\n";
+    assert_eq!(resolve_errors(source), ["E0123 4:5"]);
+}
+
+#[test]
+fn this_needs_a_class() {
+    let source = "print this;\nfun f() { return this; }\n";
+    assert_eq!(resolve_errors(source), ["E0124 1:7", "E0124 2:18"]);
+}
+
+#[test]
+fn super_needs_a_class() {
+    assert_eq!(resolve_errors("fun f() { super.g(); }\n"), ["E0125 1:11"]);
+}
+
+#[test]
+fn super_needs_a_superclass() {
+    let source = "class A {\n  m() { super.m(); }\n}\n";
+    assert_eq!(resolve_errors(source), ["E0126 2:9"]);
+}
+
+#[test]
+fn every_resolver_error_in_a_file_is_reported() {
+    let source = "return;\n{ var x = x; }\nprint this;\n";
+    assert_eq!(
+        resolve_errors(source),
+        ["E0122 1:1", "E0120 2:11", "E0124 3:7"]
+    );
+}
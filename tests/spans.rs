@@ -0,0 +1,72 @@
+mod common;
+
+use common::stderr_of;
+
+/// Every span in `--error-format=json` output, in order, as
+/// `line:column start..end`: each diagnostic's primary span, then those of its
+/// labels.
+fn spans(source: &str) -> Vec<String> {
+    let stderr = stderr_of(&["--error-format=json"], source);
+    stderr
+        .match_indices(r#""span":{"#)
+        .map(|(index, _)| {
+            let span = &stderr[index..];
+            let span = &span[..span.find('}').expect("Unterminated span")];
+            let field = |name: &str| {
+                let start = span.find(name).expect("Missing field") + name.len();
+                let end = start + span[start..].find([',', '}']).unwrap_or(span.len() - start);
+                span[start..end].to_string()
+            };
+            format!(
+                "{}:{} {}..{}",
+                field(r#""line":"#),
+                field(r#""column":"#),
+                field(r#""start":"#),
+                field(r#""end":"#)
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn lexer_errors_point_at_the_character() {
+    assert_eq!(spans("\n\n   @\n"), ["3:4 5..6"]);
+}
+
+#[test]
+fn parse_errors_point_at_the_unexpected_token() {
+    assert_eq!(spans("var a = 1\nprint a;\n"), ["2:1 10..15"]);
+}
+
+#[test]
+fn variables_span_their_name() {
+    assert_eq!(spans("print\n  undefinedName;\n"), ["2:3 8..21"]);
+}
+
+#[test]
+fn binary_errors_span_the_operator_and_label_each_operand() {
+    let source = "var x = 1;\n\n  x = x + nil;\n";
+    assert_eq!(spans(source), ["3:9 20..21", "3:7 18..19", "3:11 22..25"]);
+}
+
+#[test]
+fn operand_labels_cover_whole_expressions() {
+    let source = "var s = (\"ab\" + \"cd\") - 2 * 3;\n";
+    assert_eq!(spans(source), ["1:23 22..23", "1:9 8..21", "1:25 24..29"]);
+}
+
+#[test]
+fn unary_errors_label_their_operand() {
+    assert_eq!(spans("print -\"x\";\n"), ["1:7 6..7", "1:8 7..10"]);
+}
+
+#[test]
+fn property_errors_span_the_property_name() {
+    assert_eq!(spans("class A {}\nA().field.more;\n"), ["2:5 15..20"]);
+}
+
+#[test]
+fn spans_after_a_multi_line_string_count_its_lines() {
+    let source = "var s = \"one\ntwo\";\nprint s - 1;\n";
+    assert_eq!(spans(source), ["3:9 27..28", "3:7 25..26", "3:11 29..30"]);
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use common::{stderr_of, stdout_on_each_backend};
+
+#[test]
+fn strings_compare_by_contents() {
+    let source = r#"
+        var greeting = "hello";
+        print greeting == "hello";
+        print "hel" + "lo" == greeting;
+        print greeting == "world";
+    "#;
+    let expected = "true\ntrue\nfalse\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn copies_of_a_string_are_unaffected_by_reassignment() {
+    let source = r#"
+        var a = "one";
+        var b = a;
+        a = a + " two";
+        print a;
+        print b;
+    "#;
+    assert_eq!(stdout_on_each_backend(source), "one two\none\n");
+}
+
+#[test]
+fn escapes_stand_for_the_characters_they_name() {
+    let source = r#"
+        print "\x41\u{e9}\u{1F600}" == "Aé😀";
+        print "tab\there" == "tab	here";
+        print "say \"hi\"\\n";
+        print "\0" == "\x00";
+    "#;
+    let expected = "true\ntrue\nsay \"hi\"\\n\ntrue\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn escaped_newlines_do_not_count_as_lines() {
+    let source = "print \"one\\ntwo\";\nprint -\"three\";\n";
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(stderr.contains(" --> "), "{}", stderr);
+    assert!(stderr.contains(".lox:2:7\n"), "{}", stderr);
+}
+
+#[test]
+fn a_backslash_at_the_end_of_a_line_continues_the_string() {
+    let source = "print \"one \\\n      two\";\nprint \"a\\\nb\" == \"ab\";\n";
+    assert_eq!(stdout_on_each_backend(source), "one two\ntrue\n");
+}
+
+#[test]
+fn a_backslash_before_a_crlf_line_ending_continues_the_string() {
+    let source = "print \"a \\\r\n  b\";\r\nprint -\"c\";\r\n";
+    assert_eq!(stdout_on_each_backend(source), "a b\n");
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(stderr.contains(".lox:3:7\n"), "{}", stderr);
+}
+
+#[test]
+fn continued_strings_still_count_their_lines() {
+    let source = "print \"one \\\n  two\";\nprint -\"three\";\n";
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(stderr.contains(".lox:3:7\n"), "{}", stderr);
+}
+
+#[test]
+fn invalid_escapes_are_reported_at_their_column() {
+    let source = "var path = \"C:\\data\";\nprint \"\\u{110000}\";\n";
+    let stderr = stderr_of(&["--error-format=json"], source);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", stderr);
+    assert!(lines[0].starts_with(r#"{"code":"E0003","#), "{}", stderr);
+    assert!(
+        lines[0].contains(r#""message":"Invalid escape sequence '\\d'.""#),
+        "{}",
+        stderr
+    );
+    assert!(lines[0].contains(r#""line":1,"column":15,"#), "{}", stderr);
+    let message = r#""message":"Invalid escape sequence '\\u{110000}'.""#;
+    assert!(lines[1].contains(message), "{}", stderr);
+    assert!(lines[1].contains(r#""line":2,"column":8,"#), "{}", stderr);
+}
+
+#[test]
+fn interpolated_values_are_shown_the_way_print_shows_them() {
+    let source = r#"
+        var count = 3;
+        print "${count} items cost ${count * 2}.";
+        print "${nil}, ${true}";
+        print "count";
+    "#;
+    let expected = "3 items cost 6.\nnil, true\ncount\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn interpolated_expressions_may_contain_strings() {
+    let source = r#"
+        var n = 2;
+        print "outer ${"inner ${n + 1}" + "!"} done" == "outer inner 3! done";
+        print "\${n}" == "$" + "{n}";
+    "#;
+    let expected = "true\ntrue\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn interpolations_capture_locals() {
+    let source = r#"
+        fun greeter(name) {
+          fun greet() { return "hi ${name}"; }
+          return greet;
+        }
+        print greeter(1)();
+    "#;
+    let expected = "hi 1\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn interpolated_expressions_must_end_in_a_brace() {
+    let stderr = stderr_of(&["--color=never"], "print \"a ${1 2}\";\n");
+    let message = "error[E0127]: Expect '}' after interpolated expression.";
+    assert!(stderr.contains(message), "{}", stderr);
+    assert!(
+        stderr.contains("1 | print \"a ${1 2}\";\n  |              ^\n"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn an_unterminated_interpolation_leaves_later_braces_alone() {
+    let source = "print \"total ${1 + 2;\n{\n  print \"block\";\n}\n";
+    let stderr = stderr_of(&["--error-format=json"], source);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 1, "{}", stderr);
+    assert!(lines[0].starts_with(r#"{"code":"E0127","#), "{}", stderr);
+    assert!(lines[0].contains(r#""line":1,"column":21,"#), "{}", stderr);
+}
+
+#[test]
+fn nested_interpolations_resume_their_own_strings() {
+    let source = r#"print "a ${"b ${1 + 1} c"} d";"#;
+    assert_eq!(stdout_on_each_backend(source), "a b 2 c d\n");
+}
+
+#[test]
+fn triple_quoted_strings_lose_their_common_indentation() {
+    let source = r#"
+        var query = """
+            SELECT name
+              FROM users
+            WHERE id = 1;\n
+            """;
+        print query == "SELECT name\n  FROM users\nWHERE id = 1;\n";
+        print """on "one" line""" == "on \"one\" line";
+        print """
+          kept
+        """ == "  kept";
+    "#;
+    let expected = "true\ntrue\ntrue\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn raw_strings_keep_backslashes_and_quotes() {
+    let source = r###"
+        print r"C:\data\${x}" == "C:\\data\\\${x}";
+        print r#"say "hi""# == "say \"hi\"";
+        print r##"keeps "# inside"## == "keeps \"# inside";
+    "###;
+    let expected = "true\ntrue\ntrue\n";
+    assert_eq!(stdout_on_each_backend(source), expected);
+}
+
+#[test]
+fn lines_after_multi_line_strings_keep_their_numbers() {
+    let source = "var a = \"\"\"\n  one\n  two\n  \"\"\";\nvar b = r\"three\nfour\";\nprint -a;\n";
+    let stderr = stderr_of(&["--color=never"], source);
+    assert!(stderr.contains(".lox:7:7\n"), "{}", stderr);
+}
+
+#[test]
+fn unterminated_triple_quoted_strings_are_reported() {
+    let stderr = stderr_of(&["--color=never"], "print \"\"\"\n  never closed \"\" \n");
+    assert!(
+        stderr.contains("error[E0001]: Unterminated string."),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("1 | print \"\"\"\n  |       ^^^\n"),
+        "{}",
+        stderr
+    );
+}